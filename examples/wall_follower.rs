@@ -0,0 +1,90 @@
+//! A solver of your own in the solver menu: keeps a hand on the wall to the right until it reaches the exit.
+//!
+//! Run it with `cargo run --example wall_follower`, generate a maze, select "Wall follower" with `i` and press `o`
+
+use std::time::Duration;
+
+use daedalus::{
+    app::{
+        grid::{cell::Cell, topology::Topology, wrap::Wrap, Grid},
+        solvers::{shortest_path, ISolver, Search},
+        state::{MazeSnapshot, SharedVec},
+        App,
+    },
+    terminal,
+};
+use eyre::Result;
+
+/// Directions of the square cells, turning right at every step
+const CLOCKWISE: [Cell; 4] = [Cell::NORTH, Cell::EAST, Cell::SOUTH, Cell::WEST];
+
+struct WallFollower {
+    grid: Grid,
+    start: (usize, usize),
+    goal: (usize, usize),
+}
+
+impl ISolver for WallFollower {
+    fn init(grid: Grid, start: (usize, usize), goal: (usize, usize)) -> Self {
+        Self { grid, start, goal }
+    }
+
+    fn run(&mut self) -> Vec<MazeSnapshot> {
+        let width = self.grid.width();
+        let mut explored = SharedVec::from_elem(false, width * self.grid.height());
+        let mut path = SharedVec::from(vec![self.start]);
+        let (mut pos, mut heading) = (self.start, 1);
+        let mut snapshots = vec![];
+
+        // a wall can lead around an island forever in a braid maze, so the walk ends after every passage is taken
+        for _ in 0..self.grid.cells_count() * 4 {
+            explored[pos.1 * width + pos.0] = true;
+            let search = Search {
+                explored: explored.clone(),
+                path: path.clone(),
+                shortest: vec![],
+            };
+            snapshots
+                .push(MazeSnapshot::new(self.grid.clone(), vec![pos].into(), SharedVec::default()).with_search(search));
+
+            if pos == self.goal {
+                break;
+            }
+
+            // right, straight on, left, back
+            let turn = [1, 0, 3, 2]
+                .into_iter()
+                .map(|turn| (heading + turn) % 4)
+                .find(|turn| self.grid.is_cell_carved(pos, CLOCKWISE[*turn]));
+            let next = turn.and_then(|turn| Some((turn, self.grid.get_next_cell_pos(pos, CLOCKWISE[turn]).ok()?)));
+            match next {
+                Some((turn, next)) => {
+                    // stepping back to the previous cell leaves a dead end behind
+                    if path.len() > 1 && path[path.len() - 2] == next {
+                        path.pop();
+                    } else {
+                        path.push(next);
+                    }
+                    (pos, heading) = (next, turn);
+                }
+                None => break,
+            }
+        }
+
+        let search = Search {
+            explored,
+            path: if pos == self.goal { path } else { SharedVec::default() },
+            shortest: shortest_path(&self.grid, self.start, self.goal),
+        };
+        snapshots
+            .push(MazeSnapshot::new(self.grid.clone(), SharedVec::default(), SharedVec::default()).with_search(search));
+        snapshots
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let app = App::new("Maze Generator", 15, 10, Topology::Rectangular, None, Wrap::None, 1)
+        .with_solver::<WallFollower>("Wall follower");
+    terminal::run(Duration::from_millis(33), app).await
+}
@@ -1,8 +1,7 @@
 use std::collections::HashMap;
 use std::fmt::{self, Display};
-use std::slice::Iter;
 
-use crate::event::Key;
+use crate::{app::grid::cell::Cell, event::Key};
 
 /// We define all available action
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -11,20 +10,70 @@ pub enum Action {
     SelectNextAlgo,
     SelectPrevAlgo,
     RunMazeGeneration,
+    ToggleSetColors,
+    ToggleComparedAlgo,
+    SwitchTopology,
+    ExportSvg,
+    LevelUp,
+    LevelDown,
+    Play,
+    PlayInFog,
+    Race,
+    StopPlaying,
+    /// Moves the player with the controls in the direction, or takes the stairs going `UP` or `DOWN`
+    Move(Controls, Cell),
+    ToggleHighScores,
+    Solve,
+    SelectNextSolver,
 }
 
-impl Action {
-    /// All available actions
-    pub fn iterator() -> Iter<'static, Action> {
-        static ACTIONS: [Action; 4] = [
-            Action::Quit,
-            Action::SelectNextAlgo,
-            Action::SelectPrevAlgo,
-            Action::RunMazeGeneration,
-        ];
-        ACTIONS.iter()
+/// Keys moving a player around the maze. Racing players share the keyboard, each one using their own keys
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Controls {
+    /// The arrow keys and WASD move the only player
+    Any,
+    /// WASD move the first player, R and F take the stairs
+    Wasd,
+    /// The arrow keys move the second player, PageUp and PageDown take the stairs
+    Arrows,
+}
+
+impl Controls {
+    /// Directions the players move in, the last two take the stairs
+    pub const DIRECTIONS: [Cell; 6] = [Cell::NORTH, Cell::SOUTH, Cell::WEST, Cell::EAST, Cell::UP, Cell::DOWN];
+
+    /// The player moved by the keys
+    pub fn player(&self) -> usize {
+        match self {
+            Controls::Any | Controls::Wasd => 0,
+            Controls::Arrows => 1,
+        }
     }
 
+    fn keys(&self, direction: Cell) -> &'static [Key] {
+        match (self, direction) {
+            (Controls::Any, Cell::NORTH) => &[Key::Up, Key::Char('w')],
+            (Controls::Any, Cell::SOUTH) => &[Key::Down, Key::Char('s')],
+            (Controls::Any, Cell::WEST) => &[Key::Left, Key::Char('a')],
+            (Controls::Any, Cell::EAST) => &[Key::Right, Key::Char('d')],
+            (Controls::Wasd, Cell::NORTH) => &[Key::Char('w')],
+            (Controls::Wasd, Cell::SOUTH) => &[Key::Char('s')],
+            (Controls::Wasd, Cell::WEST) => &[Key::Char('a')],
+            (Controls::Wasd, Cell::EAST) => &[Key::Char('d')],
+            (Controls::Wasd, Cell::UP) => &[Key::Char('r')],
+            (Controls::Wasd, Cell::DOWN) => &[Key::Char('f')],
+            (Controls::Arrows, Cell::NORTH) => &[Key::Up],
+            (Controls::Arrows, Cell::SOUTH) => &[Key::Down],
+            (Controls::Arrows, Cell::WEST) => &[Key::Left],
+            (Controls::Arrows, Cell::EAST) => &[Key::Right],
+            (_, Cell::UP) => &[Key::PageUp],
+            (_, Cell::DOWN) => &[Key::PageDown],
+            _ => &[],
+        }
+    }
+}
+
+impl Action {
     /// List of key associated to action
     pub fn keys(&self) -> &[Key] {
         match self {
@@ -32,6 +81,20 @@ impl Action {
             Action::SelectNextAlgo => &[Key::Down],
             Action::SelectPrevAlgo => &[Key::Up],
             Action::RunMazeGeneration => &[Key::Enter],
+            Action::ToggleSetColors => &[Key::Char('c')],
+            Action::ToggleComparedAlgo => &[Key::Char(' ')],
+            Action::SwitchTopology => &[Key::Char('g')],
+            Action::ExportSvg => &[Key::Char('e')],
+            Action::LevelUp => &[Key::PageUp],
+            Action::LevelDown => &[Key::PageDown],
+            Action::Play => &[Key::Char('p')],
+            Action::PlayInFog => &[Key::Char('f')],
+            Action::Race => &[Key::Char('r')],
+            Action::StopPlaying => &[Key::Esc],
+            Action::Move(controls, direction) => controls.keys(*direction),
+            Action::ToggleHighScores => &[Key::Char('h')],
+            Action::Solve => &[Key::Char('o')],
+            Action::SelectNextSolver => &[Key::Char('i')],
         }
     }
 }
@@ -39,11 +102,41 @@ impl Action {
 /// Could display a user friendly short description of action
 impl Display for Action {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Action::Move(controls, direction) = self {
+            let movement = match *direction {
+                Cell::NORTH => "move up",
+                Cell::SOUTH => "move down",
+                Cell::WEST => "move left",
+                Cell::EAST => "move right",
+                Cell::UP => "take the stairs up",
+                Cell::DOWN => "take the stairs down",
+                _ => "move",
+            };
+            return match controls {
+                Controls::Any => write!(f, "{}{}", movement[..1].to_uppercase(), &movement[1..]),
+                _ => write!(f, "Player {}: {}", controls.player() + 1, movement),
+            };
+        }
+
         let str = match self {
             Action::Quit => "Quit",
             Action::SelectNextAlgo => "Select next algorithm",
             Action::SelectPrevAlgo => "Select prev algorithm",
             Action::RunMazeGeneration => "Run maze generation process",
+            Action::ToggleSetColors => "Toggle set colors (Kruskal's, Eller's)",
+            Action::ToggleComparedAlgo => "Mark algorithm to compare side by side",
+            Action::SwitchTopology => "Switch grid topology (squares, hexagons, triangles, rings)",
+            Action::ExportSvg => "Export maze to SVG",
+            Action::LevelUp => "Show the level above",
+            Action::LevelDown => "Show the level below",
+            Action::Play => "Play the maze from the top left to the bottom right cell",
+            Action::PlayInFog => "Play the maze hidden in fog",
+            Action::Race => "Race another player to the exit (WASD vs arrow keys)",
+            Action::StopPlaying => "Stop playing",
+            Action::ToggleHighScores => "Show high scores of the maze",
+            Action::Solve => "Solve the mazes with the selected solver",
+            Action::SelectNextSolver => "Select next solver",
+            Action::Move(..) => "",
         };
         write!(f, "{}", str)
     }
@@ -56,9 +149,7 @@ pub struct Actions(Vec<Action>);
 impl Actions {
     /// Given a key, find the corresponding action
     pub fn find(&self, key: Key) -> Option<&Action> {
-        Action::iterator()
-            .filter(|action| self.0.contains(action))
-            .find(|action| action.keys().contains(&key))
+        self.0.iter().find(|action| action.keys().contains(&key))
     }
 
     /// Get contextual actions.
@@ -1,4 +1,4 @@
-use crate::app::{grid::cell::Cell, state::MazeSnapshot, utils::random::get_start_pos};
+use crate::app::{grid::Grid, state::MazeSnapshot, utils::random::get_start_pos};
 use rand::prelude::*;
 
 use super::{Generator, IGenerator, Snapshot};
@@ -8,26 +8,23 @@ pub struct AldousBroder {
 }
 
 impl IGenerator for AldousBroder {
-    fn init(width: usize, height: usize) -> Self {
-        let generator = Generator::new(width, height);
+    fn init(grid: Grid, seed: u64) -> Self {
+        let generator = Generator::new(grid, seed);
         Self { generator }
     }
 
     fn run(&mut self) -> Vec<MazeSnapshot> {
-        let width = self.generator.grid.width();
-        let height = self.generator.grid.height();
-
-        let (mut x, mut y) = get_start_pos(width, height);
+        let (mut x, mut y) = get_start_pos(&mut self.generator.rng, &self.generator.grid);
 
         // The number of remaining unvisited cells
-        let mut remaining = width * height - 1;
+        let mut remaining = self.generator.grid.cells_count() - 1;
         while remaining > 0 {
             self.generator.highlights.clear();
             self.generator.highlights.push((x, y));
             self.generator.make_snapshot();
 
-            let mut directions = [Cell::NORTH, Cell::SOUTH, Cell::WEST, Cell::EAST];
-            directions.shuffle(&mut rand::thread_rng());
+            let mut directions = self.generator.grid.directions((x, y));
+            directions.shuffle(&mut self.generator.rng);
 
             for dir in directions {
                 if let Ok((nx, ny)) = self.generator.grid.get_next_cell_pos((x, y), dir) {
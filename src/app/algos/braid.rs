@@ -0,0 +1,136 @@
+use crate::app::{grid::Grid, state::MazeSnapshot, utils::types::Pos};
+use rand::prelude::*;
+
+use super::{Generator, Snapshot};
+
+/// Post-processes a finished maze: removes dead ends by carving them into a neighbour, which closes loops and gives
+/// the maze more than one solution
+pub struct Braid {
+    generator: Generator,
+    /// The share of the dead ends to remove, from 0 (a perfect maze) to 1 (a braid maze)
+    ratio: f64,
+}
+
+impl Braid {
+    pub fn new(grid: Grid, seed: u64, ratio: f64) -> Self {
+        Self {
+            generator: Generator::new(grid, seed),
+            ratio: ratio.clamp(0.0, 1.0),
+        }
+    }
+
+    /// Removes the dead ends in random order until the ratio is reached. A dead end is carved into another dead end
+    /// if it has one next to it, so both go at once. Returns a snapshot for every carved passage
+    pub fn run(&mut self) -> Vec<MazeSnapshot> {
+        let mut dead_ends = self
+            .generator
+            .grid
+            .positions()
+            .filter(|pos| self.is_dead_end(*pos))
+            .collect::<Vec<_>>();
+        dead_ends.shuffle(&mut self.generator.rng);
+
+        let mut left = dead_ends.len();
+        let target = dead_ends.len() - (dead_ends.len() as f64 * self.ratio).round() as usize;
+
+        for pos in dead_ends {
+            if left <= target {
+                break;
+            }
+            // an earlier passage could have been carved into this dead end already
+            if !self.is_dead_end(pos) {
+                continue;
+            }
+
+            let grid = &self.generator.grid;
+            let links = grid.links(pos);
+            let neighbours = grid
+                .neighbours(pos)
+                .into_iter()
+                .filter(|npos| !links.contains(npos))
+                .collect::<Vec<_>>();
+            let dead_end_neighbours = neighbours
+                .iter()
+                .copied()
+                .filter(|npos| self.is_dead_end(*npos))
+                .collect::<Vec<_>>();
+
+            let candidates = match dead_end_neighbours.is_empty() {
+                true => neighbours,
+                false => dead_end_neighbours,
+            };
+            let npos = match candidates.choose(&mut self.generator.rng) {
+                Some(npos) => *npos,
+                None => continue,
+            };
+
+            left -= if self.is_dead_end(npos) { 2 } else { 1 };
+            if self.generator.grid.link(pos, npos).is_err() {
+                continue;
+            }
+
+            self.generator.highlights = vec![pos, npos].into();
+            self.generator.make_snapshot();
+        }
+
+        self.generator.highlights.clear();
+        self.generator.make_snapshot();
+
+        self.generator.get_snapshots()
+    }
+
+    fn is_dead_end(&self, pos: Pos) -> bool {
+        self.generator.grid.carved_directions(pos).len() == 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        algos::{IGenerator, Kruskal, RecursiveBacktracking},
+        grid::validation::validate_perfect_maze,
+    };
+
+    fn dead_ends(grid: &Grid) -> usize {
+        grid.positions()
+            .filter(|pos| grid.carved_directions(*pos).len() == 1)
+            .count()
+    }
+
+    #[test]
+    fn full_braid_removes_every_dead_end() {
+        let maze = RecursiveBacktracking::init(Grid::new(10, 8), 7).run();
+        let maze = maze.last().unwrap().get_grid();
+        assert!(dead_ends(maze) > 0);
+
+        let snapshots = Braid::new(maze.clone(), 7, 1.0).run();
+        let braid = snapshots.last().unwrap().get_grid();
+
+        assert_eq!(dead_ends(braid), 0);
+        assert!(validate_perfect_maze(braid).is_err());
+        assert!(snapshots.len() > 1 && snapshots.len() <= dead_ends(maze) + 1);
+    }
+
+    #[test]
+    fn partial_braid_keeps_the_rest_of_the_dead_ends() {
+        let maze = Kruskal::init(Grid::new(12, 12), 3).run();
+        let maze = maze.last().unwrap().get_grid();
+        let before = dead_ends(maze);
+
+        let snapshots = Braid::new(maze.clone(), 3, 0.5).run();
+        let after = dead_ends(snapshots.last().unwrap().get_grid());
+
+        assert!(after < before && after + 1 >= before / 2, "{} -> {}", before, after);
+        assert_eq!(dead_ends(Braid::new(maze.clone(), 3, 0.0).run()[0].get_grid()), before);
+    }
+
+    #[test]
+    fn dead_ends_without_free_neighbours_are_kept() {
+        let maze = Kruskal::init(Grid::new(1, 2), 1).run();
+        let snapshots = Braid::new(maze.last().unwrap().get_grid().clone(), 1, 1.0).run();
+
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(dead_ends(snapshots[0].get_grid()), 2);
+    }
+}
@@ -1,189 +1,131 @@
 use crate::app::{
+    grid::{cell::Cell, Grid},
     state::MazeSnapshot,
-    utils::types::Pos,
-    grid::cell::Cell as GridCell,
+    utils::arena::{ArenaTree, NodeId},
 };
 use rand::prelude::*;
 
 use super::{Generator, IGenerator, Snapshot};
 
-use std::{cell::RefCell, collections::HashMap};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct CellId(usize);
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct SetId(usize);
-
-#[derive(Debug, Clone, Copy)]
-struct Cell {
-    _id: CellId,
-    set_id: SetId,
-    pos: Pos,
-}
-
-struct State {
-    width: usize,
-    next_set_id: Option<usize>,
-    row_num: usize,
-    cells: HashMap<CellId, RefCell<Cell>>,
-}
-
-impl State {
-    fn new(row_num: usize, next_set_id: Option<usize>, width: usize) -> State {
-        State {
-            width,
-            next_set_id,
-            row_num,
-            cells: HashMap::new(),
-        }
-    }
-
-    fn next(&self) -> State {
-        State::new(self.row_num + 1, self.next_set_id, self.width)
-    }
-
-    fn populate(mut self) -> Self {
-        for n in 1..=self.width {
-            let cell_id = CellId(n);
-            if self.cells.get(&cell_id).is_some() {
-                continue;
-            }
-
-            self.next_set_id = Some(self.next_set_id.unwrap_or(0) + 1);
-            let set_id = SetId(self.next_set_id.unwrap());
-
-            self.add(cell_id, set_id, (n - 1, self.row_num));
-        }
-
-        self
-    }
-
-    fn add(&mut self, _id: CellId, set_id: SetId, pos: Pos) {
-        let cell = Cell { _id, set_id, pos };
-        self.cells.insert(_id, RefCell::new(cell));
-    }
-
-    fn connect(&mut self, sink_id: CellId, target_id: CellId) {
-        let sink = self.cells.get(&sink_id).unwrap().borrow();
-        let mut target = self.cells.get(&target_id).unwrap().borrow_mut();
-        (*target).set_id = (*sink).set_id;
-    }
-
-    fn connected(&self, id: CellId, other_id: CellId) -> bool {
-        let cell = self.cells.get(&id).unwrap().borrow();
-        let other = self.cells.get(&other_id).unwrap().borrow();
-        (*cell).set_id == (*other).set_id
-    }
-
-    fn get_cell_pos(&self, id: CellId) -> Pos {
-        let cell = self.cells.get(&id).unwrap().borrow();
-        (*cell).pos
-    }
-
-    fn sets(&self) -> HashMap<SetId, Vec<CellId>> {
-        let mut sets: HashMap<SetId, Vec<CellId>> = HashMap::new();
-
-        self.cells.iter().for_each(|(id, cell)| {
-            let cell = cell.borrow();
-
-            if let Some(cells) = sets.get_mut(&cell.set_id) {
-                (*cells).push(*id);
-            } else {
-                sets.insert(cell.set_id, vec![*id]);
-            }
-        });
-
-        sets
-    }
-}
-
 pub struct Eller {
     generator: Generator,
+    /// Sets of the cells in the current row, where a cell's node id is its x. It starts over for every row
+    arena: ArenaTree,
+    /// Set id of every set in the row by its root. The sets carried down to the next row keep their ids
+    row_sets: Vec<u32>,
+    next_set: u32,
 }
 
 impl Eller {
     /// Randomly joins adjacent cells, but only if they are not in the same set
-    fn connect_disjoint_sets(&mut self, state: &mut State, is_last_row: bool) {
-        let mut rng = rand::thread_rng();
-
-        for c in 1..state.width {
-            let cell_id = CellId(c);
-            let next_cell_id = CellId(c + 1);
+    fn connect_disjoint_sets(&mut self, y: usize, is_last_row: bool) {
+        for x in 1..self.generator.grid.width() {
+            let (node1, node2) = (NodeId(x - 1), NodeId(x));
 
-            if state.connected(cell_id, next_cell_id) || (!is_last_row && rng.gen::<bool>()) {
+            if self.arena.connected(node1, node2) || (!is_last_row && self.generator.rng.gen::<bool>()) {
                 continue;
             }
 
-            state.connect(cell_id, next_cell_id);
-            let (x, y) = state.get_cell_pos(cell_id);
-            self.generator.grid.carve_passage((x, y), GridCell::EAST).unwrap();
+            self.connect(node1, node2);
+            self.generator.grid.carve_passage((x - 1, y), Cell::EAST).unwrap();
 
-            self.generator.highlights.push((x, y));
+            self.generator.highlights.push((x - 1, y));
             self.generator.make_snapshot();
         }
     }
 
     /// For each set, creates at least one vertical connection downward to the next row
-    fn add_vertical_connections(&mut self, state: &mut State, is_last_row: bool) -> State {
-        let mut next_state = state.next();
-
-        if is_last_row {
-            return next_state.populate();
+    fn add_vertical_connections(&mut self, y: usize) {
+        let mut sets = vec![];
+        for nodes in self.arena.sets() {
+            let root = self.arena.root(nodes[0]).unwrap();
+            sets.push((self.row_sets[root.0], nodes.iter().map(|node| node.0).collect()));
         }
 
-        for (set_id, cells) in state.sets() {
-            for cell_id in self.cells_to_connect(cells) {
-                let (x, y) = state.get_cell_pos(cell_id);
-                self.generator.grid.carve_passage((x, y), GridCell::SOUTH).unwrap();
-                next_state.add(cell_id, set_id, (x, y + 1));
+        // cells of the next row start in their own sets
+        self.start_row(y + 1);
+
+        for (set, cells) in sets {
+            let mut carried: Option<NodeId> = None;
+            for x in self.cells_to_connect(cells) {
+                self.generator.grid.carve_passage((x, y), Cell::SOUTH).unwrap();
+                self.generator.merge_sets(self.row_sets[x], set);
+                let root = match carried {
+                    Some(node) => self.arena.connect(node, NodeId(x)).unwrap(),
+                    None => NodeId(x),
+                };
+                self.row_sets[root.0] = set;
+                carried = Some(root);
 
                 self.generator.highlights.push((x, y));
                 self.generator.highlights.push((x, y + 1));
                 self.generator.make_snapshot();
             }
         }
-
-        next_state.populate()
     }
 
     /// Selects random cells to carve vertical passages from
-    fn cells_to_connect(&self, cells: Vec<CellId>) -> Vec<CellId> {
-        let mut rng = rand::thread_rng();
-
-        let mut cells = cells.clone();
-        cells.shuffle(&mut rng);
+    fn cells_to_connect(&mut self, cells: Vec<usize>) -> Vec<usize> {
+        let mut cells = cells;
+        cells.shuffle(&mut self.generator.rng);
 
         let connect_count = if cells.len() >= 2 {
-            rng.gen_range(1..cells.len())
+            self.generator.rng.gen_range(1..cells.len())
         } else {
             1
         };
 
-        cells
-            .iter()
-            .take(connect_count)
-            .cloned()
-            .collect::<Vec<CellId>>()
+        cells.into_iter().take(connect_count).collect()
+    }
+
+    /// Puts every cell of the row in a new set of its own
+    fn start_row(&mut self, y: usize) {
+        self.arena.reset();
+        for x in 0..self.generator.grid.width() {
+            self.row_sets[x] = self.next_set;
+            self.generator.add_to_set((x, y), self.next_set);
+            self.next_set += 1;
+        }
+    }
+
+    /// Merges the sets of two cells in the row. The merged set keeps the id, and the color, of the bigger one
+    fn connect(&mut self, node1: NodeId, node2: NodeId) {
+        let (root1, root2) = match (self.arena.root(node1), self.arena.root(node2)) {
+            (Some(root1), Some(root2)) => (root1, root2),
+            _ => return,
+        };
+        if let Some(root) = self.arena.connect(root1, root2) {
+            let absorbed = if root == root1 { root2 } else { root1 };
+            let (from, into) = (self.row_sets[absorbed.0], self.row_sets[root.0]);
+            self.generator.merge_sets(from, into);
+        }
     }
 }
 
 impl IGenerator for Eller {
-    fn init(width: usize, height: usize) -> Self {
-        let generator = Generator::new(width, height);
-        Self { generator }
+    fn init(grid: Grid, seed: u64) -> Self {
+        let width = grid.width();
+        let generator = Generator::new(grid, seed);
+        Self {
+            generator,
+            arena: ArenaTree::with_nodes(width),
+            row_sets: vec![0; width],
+            next_set: 0,
+        }
     }
 
     fn run(&mut self) -> Vec<MazeSnapshot> {
-        let width = self.generator.grid.width();
         let height = self.generator.grid.height();
 
-        let mut state = State::new(0, None, width).populate();
+        self.generator.track_sets();
+        self.start_row(0);
 
         for row in 0..height {
             let is_last_row = row == height - 1;
-            self.connect_disjoint_sets(&mut state, is_last_row);
-            state = self.add_vertical_connections(&mut state, is_last_row);
+            self.connect_disjoint_sets(row, is_last_row);
+            if !is_last_row {
+                self.add_vertical_connections(row);
+            }
 
             self.generator.highlights.clear();
             self.generator.make_snapshot();
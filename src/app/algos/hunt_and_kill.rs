@@ -1,7 +1,7 @@
 use crate::app::{
     state::MazeSnapshot,
     utils::{random::get_start_pos, types::Pos},
-    grid::cell::Cell,
+    grid::Grid,
 };
 use rand::prelude::*;
 
@@ -15,8 +15,8 @@ pub struct HuntAndKill {
 
 impl HuntAndKill {
     fn walk(&mut self, pos: Pos) -> Option<Pos> {
-        let mut directions = [Cell::NORTH, Cell::SOUTH, Cell::WEST, Cell::EAST];
-        directions.shuffle(&mut rand::thread_rng());
+        let mut directions = self.generator.grid.directions(pos);
+        directions.shuffle(&mut self.generator.rng);
 
         for dir in directions {
             if let Ok(next_pos) = self.generator.grid.get_next_cell_pos(pos, dir) {
@@ -30,29 +30,27 @@ impl HuntAndKill {
     }
 
     fn hunt(&mut self) -> Option<Pos> {
-        let directions = [Cell::NORTH, Cell::SOUTH, Cell::WEST, Cell::EAST];
-
         for y in self.hunt_start_index..self.generator.grid.height() {
-            for x in 0..self.generator.grid.width() {
+            for x in 0..self.generator.grid.row_width(y) {
                 self.generator.highlights.push((x, y));
             }
 
             self.generator.make_snapshot();
 
-            for _ in 0..self.generator.grid.width() {
+            for _ in 0..self.generator.grid.row_width(y) {
                 self.generator.highlights.pop();
             }
 
             let mut unvisited_cells_count = 0;
 
-            for x in 0..self.generator.grid.width() {
-                if self.generator.grid.is_cell_visited((x, y)) {
+            for x in 0..self.generator.grid.row_width(y) {
+                if !self.generator.grid.contains((x, y)) || self.generator.grid.is_cell_visited((x, y)) {
                     continue;
                 } else {
                     unvisited_cells_count += 1;
                 }
 
-                for dir in directions {
+                for dir in self.generator.grid.directions((x, y)) {
                     if let Ok(next_pos) = self.generator.grid.get_next_cell_pos((x, y), dir) {
                         if self.generator.grid.is_cell_visited(next_pos) {
                             self.generator.grid.carve_passage((x, y), dir).ok();
@@ -72,8 +70,8 @@ impl HuntAndKill {
 }
 
 impl IGenerator for HuntAndKill {
-    fn init(width: usize, height: usize) -> Self {
-        let generator = Generator::new(width, height);
+    fn init(grid: Grid, seed: u64) -> Self {
+        let generator = Generator::new(grid, seed);
         Self {
             generator,
             hunt_start_index: 0,
@@ -82,7 +80,7 @@ impl IGenerator for HuntAndKill {
     }
 
     fn run(&mut self) -> Vec<MazeSnapshot> {
-        let start_pos = get_start_pos(self.generator.grid.width(), self.generator.grid.height());
+        let start_pos = get_start_pos(&mut self.generator.rng, &self.generator.grid);
         let mut x = start_pos.0;
         let mut y = start_pos.1;
 
@@ -1,5 +1,5 @@
 use crate::app::{
-    grid::cell::Cell,
+    grid::{cell::Cell, Grid},
     state::MazeSnapshot,
     utils::{
         arena::{ArenaTree, NodeId},
@@ -15,45 +15,132 @@ type Edges = Vec<Edge>;
 
 pub struct Kruskal {
     generator: Generator,
+    /// The chance of every cell to get a crossing before the cells are joined
+    crossings: f64,
 }
 
 impl Kruskal {
-    fn populate_arena_tree(&self) -> ArenaTree {
-        let mut arena = ArenaTree::new();
-        for _ in 0..self.generator.grid.width() * self.generator.grid.height() {
-            arena.new_node();
-        }
-        arena
+    /// Weaves the maze: passages cross over and under each other at random cells before the rest of the maze is
+    /// joined. Only square cells can be crossed
+    pub fn with_crossings(mut self, density: f64) -> Self {
+        self.crossings = density;
+        self
     }
 
+    /// Lists every pair of adjacent cells once, as the passage leading from the later cell to the earlier one.
+    /// Crossings have all their passages from the start
     fn populate_edges(&self) -> Edges {
+        let grid = &self.generator.grid;
+        let is_crossing = |pos: Pos| grid.is_cell_carved(pos, Cell::UNDER);
         let mut edges: Edges = vec![];
-        for y in 0..self.generator.grid.height() {
-            for x in 0..self.generator.grid.width() {
-                if y > 0 {
-                    edges.push((x, y, Cell::NORTH))
-                }
-                if x > 0 {
-                    edges.push((x, y, Cell::WEST))
+        for (x, y) in grid.positions().filter(|pos| !is_crossing(*pos)) {
+            for dir in grid.directions((x, y)) {
+                if let Ok((nx, ny)) = grid.get_next_cell_pos((x, y), dir) {
+                    if (ny, nx) < (y, x) && !is_crossing((nx, ny)) {
+                        edges.push((x, y, dir))
+                    }
                 }
             }
         }
         edges
     }
+
+    /// Places crossings over cells whose neighbours aren't crossings themselves and where the passage over the cell
+    /// and the tunnel under it join cells that aren't connected yet
+    fn add_crossings(&mut self, arena: &mut ArenaTree) {
+        let width = self.generator.grid.width();
+        let node = |(x, y): Pos| NodeId(y * width + x);
+        let positions = self.generator.grid.positions().collect::<Vec<_>>();
+
+        for pos in positions {
+            if !self.generator.rng.gen_bool(self.crossings) {
+                continue;
+            }
+
+            let (over, under) = if self.generator.rng.gen() {
+                (Cell::EAST, Cell::SOUTH)
+            } else {
+                (Cell::SOUTH, Cell::EAST)
+            };
+            let grid = &self.generator.grid;
+            let ends = [over, over.opposite(), under, under.opposite()]
+                .iter()
+                .map(|dir| grid.get_next_cell_pos(pos, *dir).ok())
+                .collect::<Option<Vec<_>>>();
+            let ends = match ends {
+                Some(ends) if ends.iter().all(|end| !grid.is_cell_carved(*end, Cell::UNDER)) => ends,
+                _ => continue,
+            };
+            // on narrow wrapped grids the ends of the tunnel can already be adjacent around the edge
+            if !grid.carved_directions(pos).is_empty() || grid.neighbours(ends[2]).contains(&ends[3]) {
+                continue;
+            }
+
+            // the passage over the cell merges the sets of its ends, the tunnel must not join that set with itself
+            let over_sets = [ends[0], ends[1]].map(|end| arena.root(node(end)));
+            let under_sets = [ends[2], ends[3]].map(|end| arena.root(node(end)));
+            if over_sets[0] == over_sets[1]
+                || under_sets[0] == under_sets[1]
+                || under_sets.iter().all(|set| over_sets.contains(set))
+            {
+                continue;
+            }
+
+            if self.generator.grid.add_crossing(pos, over).is_err() {
+                continue;
+            }
+            self.connect(arena, node(pos), node(ends[0]));
+            self.connect(arena, node(pos), node(ends[1]));
+            self.connect(arena, node(ends[2]), node(ends[3]));
+
+            self.generator.highlights = vec![pos, ends[2], ends[3]].into();
+            self.generator.make_snapshot();
+        }
+    }
+
+    /// Merges the sets of two cells. A set is identified by its root, so the merged set keeps the color of the
+    /// bigger one
+    fn connect(&mut self, arena: &mut ArenaTree, node1: NodeId, node2: NodeId) {
+        let (root1, root2) = match (arena.root(node1), arena.root(node2)) {
+            (Some(root1), Some(root2)) => (root1, root2),
+            _ => return,
+        };
+        if let Some(root) = arena.connect(root1, root2) {
+            let absorbed = if root == root1 { root2 } else { root1 };
+            self.generator.merge_sets(absorbed.0 as u32, root.0 as u32);
+        }
+    }
 }
 
 impl IGenerator for Kruskal {
-    fn init(width: usize, height: usize) -> Self {
-        let generator = Generator::new(width, height);
-        Self { generator }
+    fn init(grid: Grid, seed: u64) -> Self {
+        let generator = Generator::new(grid, seed);
+        Self {
+            generator,
+            crossings: 0.0,
+        }
     }
 
     fn run(&mut self) -> Vec<MazeSnapshot> {
-        let mut arena = self.populate_arena_tree();
+        let cells_count = self.generator.grid.width() * self.generator.grid.height();
+        // positions past the end of the narrower rows stay in their own sets
+        let holes_count = cells_count - self.generator.grid.cells_count();
+        let mut arena = ArenaTree::with_nodes(cells_count);
+
+        // every cell starts in its own set identified by the cell's node id
+        self.generator.track_sets();
+        let width = self.generator.grid.width();
+        for (x, y) in self.generator.grid.positions().collect::<Vec<_>>() {
+            self.generator.add_to_set((x, y), (y * width + x) as u32);
+        }
+        if self.crossings > 0.0 {
+            self.add_crossings(&mut arena);
+        }
         let mut edges: Edges = self.populate_edges();
 
-        edges.shuffle(&mut thread_rng());
-        while edges.len() > 0 {
+        edges.shuffle(&mut self.generator.rng);
+        // once every cell is in the same set the rest of the edges would only make loops
+        while arena.sets_count() > holes_count + 1 {
             let edge: Option<Edge> = edges.pop();
             if edge.is_none() {
                 break;
@@ -75,7 +162,7 @@ impl IGenerator for Kruskal {
                 self.generator.highlights.push((nx, ny) as Pos);
                 self.generator.make_snapshot();
 
-                arena.connect(node1, node2);
+                self.connect(&mut arena, node1, node2);
                 self.generator.grid.carve_passage((x, y), dir).unwrap();
             }
         }
@@ -1,20 +1,33 @@
-use super::{grid::Grid, state::MazeSnapshot, utils::types::Pos};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use super::{
+    grid::Grid,
+    state::{MazeSnapshot, SharedVec, NO_SET},
+    utils::types::Pos,
+};
 
 pub mod aldous_broder;
+pub mod braid;
 pub mod eller;
 pub mod hunt_and_kill;
 pub mod kruskal;
 pub mod prim;
 pub mod recursive_backtracking;
+pub mod registry;
 pub mod sidewinder;
+pub mod true_prim;
 
 pub use aldous_broder::AldousBroder;
+pub use braid::Braid;
 pub use eller::Eller;
 pub use hunt_and_kill::HuntAndKill;
 pub use kruskal::Kruskal;
 pub use prim::Prim;
 pub use recursive_backtracking::RecursiveBacktracking;
+pub use registry::{find, generate, generators, About, Descriptor, Factory, Parameter};
 pub use sidewinder::Sidewinder;
+pub use true_prim::TruePrim;
 
 pub trait Snapshot {
     fn make_snapshot(&mut self);
@@ -23,17 +36,63 @@ pub trait Snapshot {
 
 pub struct Generator {
     grid: Grid,
-    highlights: Vec<Pos>,
+    highlights: SharedVec<Pos>,
+    /// Set id of every cell for algorithms that grow disjoint sets, `NO_SET` until it's shown. Left empty by the others
+    sets: SharedVec<u32>,
+    /// Cells of every set by its id, so a merge only relabels the cells of the absorbed set
+    set_members: Vec<Vec<usize>>,
     snapshots: Vec<MazeSnapshot>,
+    /// Unlike `StdRng`, ChaCha8 promises the same stream for a seed across rand releases
+    rng: ChaCha8Rng,
 }
 
 impl Generator {
-    pub fn new(width: usize, height: usize) -> Self {
+    pub fn new(grid: Grid, seed: u64) -> Self {
         Self {
-            grid: Grid::new(width, height),
-            highlights: vec![],
+            grid,
+            highlights: SharedVec::default(),
+            sets: SharedVec::default(),
+            set_members: vec![],
             snapshots: vec![],
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Starts the layer of the sets grown by the algorithm. A cell is shown once it's added to a set
+    fn track_sets(&mut self) {
+        self.sets = SharedVec::from_elem(NO_SET, self.grid.width() * self.grid.height());
+        self.set_members = vec![];
+    }
+
+    /// Shows the cell in the set
+    fn add_to_set(&mut self, (x, y): Pos, set: u32) {
+        if !self.grid.contains((x, y)) {
+            return;
+        }
+
+        let idx = y * self.grid.width() + x;
+        self.sets[idx] = set;
+        self.members_mut(set).push(idx);
+    }
+
+    /// Moves the cells of a set into another one, which keeps its color. Only the moved cells are relabelled
+    fn merge_sets(&mut self, from: u32, into: u32) {
+        if from == into {
+            return;
         }
+
+        let members = std::mem::take(self.members_mut(from));
+        for idx in members.iter() {
+            self.sets[*idx] = into;
+        }
+        self.members_mut(into).extend(members);
+    }
+
+    fn members_mut(&mut self, set: u32) -> &mut Vec<usize> {
+        if self.set_members.len() <= set as usize {
+            self.set_members.resize(set as usize + 1, vec![]);
+        }
+        &mut self.set_members[set as usize]
     }
 }
 
@@ -42,6 +101,7 @@ impl Snapshot for Generator {
         self.snapshots.push(MazeSnapshot::new(
             self.grid.clone(),
             self.highlights.clone(),
+            self.sets.clone(),
         ));
     }
 
@@ -51,6 +111,189 @@ impl Snapshot for Generator {
 }
 
 pub trait IGenerator {
-    fn init(width: usize, height: usize) -> Self;
+    /// Prepares a generator for an empty grid. The same seed always produces the same maze
+    fn init(grid: Grid, seed: u64) -> Self;
     fn run(&mut self) -> Vec<MazeSnapshot>;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{find, generators};
+    use crate::app::{
+        grid::{cell::Cell, mask::Mask, topology::Topology, validation::validate_perfect_maze, wrap::Wrap, Grid},
+        state::NO_SET,
+        stats::distances,
+    };
+
+    const SIZES: [(usize, usize); 8] = [(1, 1), (1, 2), (1, 9), (2, 1), (9, 1), (2, 2), (7, 3), (16, 16)];
+    /// Polar grids ignore the width, the outer rings of 16 would only slow the tests down
+    const RINGS: [usize; 4] = [1, 2, 3, 9];
+    const SEEDS_COUNT: u64 = 50;
+    /// A ring with a hole and cells on the borders switched off
+    const MASK: &str = "..####...\n.#######.\n##....###\n##....###\n.#######.\n...###...";
+    /// Planar topologies supporting masks, wrapping and levels
+    const PLANAR: [Topology; 3] = [Topology::Rectangular, Topology::Hexagonal, Topology::Triangular];
+
+    fn sizes(topology: Topology) -> Vec<(usize, usize)> {
+        match topology {
+            Topology::Polar => RINGS.map(|rings| (1, rings)).to_vec(),
+            _ => SIZES.to_vec(),
+        }
+    }
+
+    /// Runs every algorithm supporting the grid with each seed and hands the final maze to `check`
+    /// along with a description of the algorithm, grid and seed
+    fn for_all_mazes(grids: impl IntoIterator<Item = (Grid, String)>, check: impl Fn(&Grid, &str)) {
+        for (grid, label) in grids {
+            for algorithm in generators() {
+                if !algorithm.supports(&grid) {
+                    continue;
+                }
+
+                for seed in 0..SEEDS_COUNT {
+                    let snapshots = algorithm.generate(grid.clone(), seed);
+                    let maze = snapshots.last().expect("no snapshots").get_grid();
+                    check(maze, &format!("{} on {} with seed {}", algorithm.name, label, seed));
+                }
+            }
+        }
+    }
+
+    fn assert_perfect(maze: &Grid, context: &str) {
+        if let Err(err) = validate_perfect_maze(maze) {
+            panic!("{} produced invalid maze: {}", context, err);
+        }
+    }
+
+    fn assert_perfect_for_all(grids: impl IntoIterator<Item = (Grid, String)>) {
+        for_all_mazes(grids, assert_perfect);
+    }
+
+    #[test]
+    fn every_algorithm_produces_perfect_maze() {
+        let mut grids = Vec::new();
+        for topology in Topology::ALL {
+            for (width, height) in sizes(topology) {
+                let label = format!("{}x{} {}", width, height, topology);
+                grids.push((Grid::with_topology(width, height, topology), label));
+            }
+        }
+        assert_perfect_for_all(grids);
+    }
+
+    #[test]
+    fn masked_cells_are_skipped() {
+        let mask = Mask::from_ascii(MASK).unwrap();
+        let grids = PLANAR.map(|topology| {
+            let label = format!("masked {}", topology);
+            (Grid::with_mask(&mask, topology).unwrap(), label)
+        });
+
+        for_all_mazes(grids, |maze, context| {
+            assert_perfect(maze, context);
+            for (x, y) in (0..maze.height()).flat_map(|y| (0..maze.width()).map(move |x| (x, y))) {
+                assert!(
+                    maze.contains((x, y)) || maze.directions((x, y)).iter().all(|d| !maze.is_cell_carved((x, y), *d)),
+                    "{} carved a masked cell",
+                    context
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn wrapped_grids_produce_perfect_mazes() {
+        let mut grids = Vec::new();
+        for topology in PLANAR {
+            for wrap in [Wrap::Horizontal, Wrap::Vertical, Wrap::Both] {
+                for (width, height) in [(3, 3), (4, 4), (8, 6)] {
+                    let label = format!("{}x{} {} wrapped {}", width, height, topology, wrap);
+                    grids.push((Grid::with_topology(width, height, topology).wrapped(wrap), label));
+                }
+            }
+        }
+        assert_perfect_for_all(grids);
+    }
+
+    #[test]
+    fn stacked_grids_produce_perfect_mazes() {
+        let mut grids = Vec::new();
+        for topology in PLANAR {
+            for (width, height, levels) in [(1, 1, 2), (3, 2, 3), (6, 5, 2)] {
+                let label = format!("{}x{}x{} {}", width, height, levels, topology);
+                grids.push((Grid::with_topology(width, height, topology).stacked(levels), label));
+            }
+        }
+        assert_perfect_for_all(grids);
+    }
+
+    #[test]
+    fn sets_follow_the_passages() {
+        for id in ["kruskal", "kruskal-weave", "eller"] {
+            for seed in 0..10 {
+                for snapshot in find(id).unwrap().generate(Grid::new(8, 6), seed) {
+                    let (grid, sets) = (snapshot.get_grid(), snapshot.get_sets());
+                    let set = |(x, y): (usize, usize)| sets[y * grid.width() + x];
+                    let shown = grid.positions().filter(|pos| set(*pos) != NO_SET).collect::<Vec<_>>();
+
+                    // every set is a single region of cells joined by passages
+                    let mut seen = vec![false; sets.len()];
+                    let mut regions = 0;
+                    for pos in shown.iter() {
+                        if seen[pos.1 * grid.width() + pos.0] {
+                            continue;
+                        }
+                        regions += 1;
+                        for (idx, distance) in distances(grid, *pos).iter().enumerate() {
+                            if distance.is_some() {
+                                seen[idx] = true;
+                                assert_eq!(sets[idx], set(*pos), "{} split a set with seed {}", id, seed);
+                            }
+                        }
+                    }
+                    let ids = shown.iter().map(|pos| set(*pos)).collect::<HashSet<_>>();
+                    assert_eq!(ids.len(), regions, "{} merged sets apart with seed {}", id, seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn weave_maze_has_crossings() {
+        let snapshots = find("kruskal-weave").unwrap().generate(Grid::new(12, 12), 1);
+        let grid = snapshots.last().unwrap().get_grid();
+
+        assert!(grid.positions().any(|pos| grid.is_cell_carved(pos, Cell::UNDER)));
+    }
+
+    #[test]
+    fn same_seed_produces_same_maze() {
+        for algorithm in generators() {
+            let grid1 = algorithm
+                .generate(Grid::new(8, 6), 42)
+                .last()
+                .unwrap()
+                .get_grid()
+                .clone();
+            let grid2 = algorithm
+                .generate(Grid::new(8, 6), 42)
+                .last()
+                .unwrap()
+                .get_grid()
+                .clone();
+
+            for y in 0..6 {
+                for x in 0..8 {
+                    assert_eq!(
+                        grid1.links((x, y)),
+                        grid2.links((x, y)),
+                        "{} is not deterministic",
+                        algorithm.name
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,123 +1,127 @@
 use crate::app::{
-    grid::cell::Cell,
-    state::MazeSnapshot,
+    grid::Grid,
+    state::{MazeSnapshot, SharedVec},
     utils::{random::get_start_pos, types::Pos},
 };
 use rand::prelude::*;
 
 use super::{Generator, IGenerator, Snapshot};
 
-pub struct Prim {
-    generator: Generator,
-    frontiers: Vec<Pos>,
+/// Cells adjacent to the maze that are not part of it yet. Every cell remembers its index in the list, so a cell
+/// can be looked up and removed in constant time. The list is shared with the snapshots that highlight it
+pub(super) struct Frontiers {
+    pub(super) cells: SharedVec<Pos>,
+    indexes: Vec<Option<usize>>,
+    width: usize,
 }
 
-impl Prim {
-    fn mark(&mut self, pos: Pos) {
-        self.generator.grid.mark_cell(pos);
+impl Frontiers {
+    pub(super) fn new(width: usize, height: usize) -> Self {
+        Self {
+            cells: SharedVec::default(),
+            indexes: vec![None; width * height],
+            width,
+        }
+    }
 
+    pub(super) fn contains(&self, (x, y): Pos) -> bool {
+        self.indexes[y * self.width + x].is_some()
+    }
+
+    pub(super) fn push(&mut self, pos: Pos) {
         let (x, y) = pos;
-        self.add_frontier((x + 1, y));
-        self.add_frontier((x, y + 1));
-        if x > 0 {
-            self.add_frontier((x - 1, y));
-        }
-        if y > 0 {
-            self.add_frontier((x, y - 1));
-        }
+        self.indexes[y * self.width + x] = Some(self.cells.len());
+        self.cells.push(pos);
     }
 
-    fn add_frontier(&mut self, (x, y): Pos) {
-        if x < self.generator.grid.width()
-            && y < self.generator.grid.height()
-            && !self.generator.grid.is_cell_marked((x, y))
-        {
-            if let None = self.frontiers.iter().position(|f| *f == (x, y)) {
-                self.frontiers.push((x, y));
+    /// Removes a cell by its index in the list, filling the gap with the last cell
+    fn swap_remove(&mut self, index: usize) -> Pos {
+        let last = self.cells.pop().unwrap();
+        let (x, y) = match index < self.cells.len() {
+            true => {
+                let (lx, ly) = last;
+                self.indexes[ly * self.width + lx] = Some(index);
+                std::mem::replace(&mut self.cells[index], last)
             }
+            false => last,
+        };
+        self.indexes[y * self.width + x] = None;
+
+        (x, y)
+    }
+
+    /// Removes the cell if it's in the list
+    pub(super) fn remove(&mut self, (x, y): Pos) {
+        if let Some(index) = self.indexes[y * self.width + x] {
+            self.swap_remove(index);
         }
     }
 
-    fn neighbours(&self, (x, y): Pos) -> Vec<Pos> {
-        let mut neighbours = vec![];
+    fn len(&self) -> usize {
+        self.cells.len()
+    }
 
-        if x > 0 && self.generator.grid.is_cell_marked((x - 1, y)) {
-            neighbours.push((x - 1, y))
-        }
+    fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
 
-        if x + 1 < self.generator.grid.width() && self.generator.grid.is_cell_marked((x + 1, y)) {
-            neighbours.push((x + 1, y))
-        }
+/// Simplified Prim's: picks a random frontier cell and connects it to a random neighbour that's in the maze
+pub struct Prim {
+    generator: Generator,
+    frontiers: Frontiers,
+}
 
-        if y > 0 && self.generator.grid.is_cell_marked((x, y - 1)) {
-            neighbours.push((x, y - 1))
-        }
+impl Prim {
+    fn mark(&mut self, pos: Pos) {
+        self.generator.grid.mark_cell(pos);
 
-        if y + 1 < self.generator.grid.height() && self.generator.grid.is_cell_marked((x, y + 1)) {
-            neighbours.push((x, y + 1))
+        for next in self.generator.grid.neighbours(pos) {
+            if !self.generator.grid.is_cell_marked(next) && !self.frontiers.contains(next) {
+                self.frontiers.push(next);
+            }
         }
+    }
 
-        neighbours
+    /// Returns the neighbours that are already in the maze
+    fn neighbours(&self, pos: Pos) -> Vec<Pos> {
+        self.generator
+            .grid
+            .neighbours(pos)
+            .into_iter()
+            .filter(|next| self.generator.grid.is_cell_marked(*next))
+            .collect()
     }
 }
 
 impl IGenerator for Prim {
-    fn init(width: usize, height: usize) -> Self {
-        let generator = Generator::new(width, height);
-        Self {
-            generator,
-            frontiers: vec![],
-        }
+    fn init(grid: Grid, seed: u64) -> Self {
+        let frontiers = Frontiers::new(grid.width(), grid.height());
+        let generator = Generator::new(grid, seed);
+        Self { generator, frontiers }
     }
 
     fn run(&mut self) -> Vec<MazeSnapshot> {
-        let mut rng = rand::thread_rng();
-
-        let start_pos = get_start_pos(self.generator.grid.width(), self.generator.grid.height());
+        let start_pos = get_start_pos(&mut self.generator.rng, &self.generator.grid);
         self.mark(start_pos);
 
-        self.generator.highlights.clear();
-        self.generator.highlights.extend(&self.frontiers);
+        self.generator.highlights = self.frontiers.cells.clone();
         self.generator.make_snapshot();
 
         while !self.frontiers.is_empty() {
-            let index = rng.gen_range(0..self.frontiers.len());
-            let pos = self.frontiers.remove(index);
+            let index = self.generator.rng.gen_range(0..self.frontiers.len());
+            let pos = self.frontiers.swap_remove(index);
 
             let neighbours = self.neighbours(pos);
 
-            let index = rng.gen_range(0..neighbours.len());
-            let (nx, ny) = neighbours[index];
+            let index = self.generator.rng.gen_range(0..neighbours.len());
+            self.generator.grid.link(pos, neighbours[index]).unwrap();
+            self.mark(pos);
 
-            let (x, y) = pos;
-
-            if let Some(dir) = direction(x, y, nx, ny) {
-                self.generator.grid.carve_passage(pos, dir).unwrap();
-                self.mark(pos);
-            }
-
-            self.generator.highlights.clear();
-            self.generator.highlights.extend(&self.frontiers);
+            self.generator.highlights = self.frontiers.cells.clone();
             self.generator.make_snapshot();
         }
 
         self.generator.get_snapshots()
     }
 }
-
-fn direction(x: usize, y: usize, nx: usize, ny: usize) -> Option<Cell> {
-    if x < nx {
-        return Some(Cell::EAST);
-    }
-    if x > nx {
-        return Some(Cell::WEST);
-    }
-    if y < ny {
-        return Some(Cell::SOUTH);
-    }
-    if y > ny {
-        return Some(Cell::NORTH);
-    }
-
-    None
-}
@@ -1,4 +1,4 @@
-use crate::app::{state::MazeSnapshot, utils::types::Pos, grid::cell::Cell};
+use crate::app::{grid::Grid, state::MazeSnapshot, utils::types::Pos};
 use rand::prelude::*;
 
 use super::{Generator, IGenerator, Snapshot};
@@ -9,8 +9,8 @@ pub struct RecursiveBacktracking {
 
 impl RecursiveBacktracking {
     fn carve_passages_from(&mut self, pos: Pos) {
-        let mut dirs = [Cell::NORTH, Cell::SOUTH, Cell::WEST, Cell::EAST];
-        dirs.shuffle(&mut rand::thread_rng());
+        let mut dirs = self.generator.grid.directions(pos);
+        dirs.shuffle(&mut self.generator.rng);
 
         for dir in dirs {
             let next = match self.generator.grid.get_next_cell_pos(pos, dir) {
@@ -34,15 +34,16 @@ impl RecursiveBacktracking {
 }
 
 impl IGenerator for RecursiveBacktracking {
-    fn init(width: usize, height: usize) -> Self {
+    fn init(grid: Grid, seed: u64) -> Self {
         Self {
-            generator: Generator::new(width, height),
+            generator: Generator::new(grid, seed),
         }
     }
 
     fn run(&mut self) -> Vec<MazeSnapshot> {
-        self.generator.highlights.push((0, 0));
-        self.carve_passages_from((0, 0));
+        let start_pos = self.generator.grid.positions().next().unwrap_or((0, 0));
+        self.generator.highlights.push(start_pos);
+        self.carve_passages_from(start_pos);
         self.generator.highlights.pop();
         self.generator.make_snapshot();
 
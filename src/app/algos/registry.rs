@@ -0,0 +1,392 @@
+use crate::app::{
+    grid::{topology::Topology, Grid},
+    state::MazeSnapshot,
+};
+
+use super::{AldousBroder, Eller, HuntAndKill, IGenerator, Kruskal, Prim, RecursiveBacktracking, Sidewinder, TruePrim};
+
+/// Carves a maze in the empty grid with the seed and the values of the generator parameters, in their order
+pub type Factory = fn(Grid, u64, &[f64]) -> Vec<MazeSnapshot>;
+
+/// A number tuning a generator, set on the command line with `--param name=value`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub default: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+/// What the algorithm panel tells about a generator while it's highlighted in the menu
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct About {
+    pub description: &'static str,
+    /// Steps of the algorithm, a line each. Nested steps are indented with two spaces
+    pub pseudocode: &'static [&'static str],
+    /// The texture of the mazes the algorithm generates
+    pub bias: &'static str,
+    /// Time complexity, where n is the number of cells
+    pub time: &'static str,
+    /// Memory complexity on top of the grid, where n is the number of cells
+    pub memory: &'static str,
+    /// What the highlighted cells are at every step
+    pub highlights: &'static str,
+    /// What the colored sets of cells are, for the algorithms growing disjoint sets
+    pub sets: Option<&'static str>,
+}
+
+/// Everything the menu, the command line and the benchmark know about a generator. Register one of your own with
+/// `App::with_generator`
+#[derive(Debug, Clone)]
+pub struct Descriptor {
+    /// The title in the menu
+    pub name: &'static str,
+    /// Selects the generator on the command line
+    pub id: &'static str,
+    pub about: About,
+    pub factory: Factory,
+    /// Grids the generator can carve
+    pub topologies: &'static [Topology],
+    /// Whether the generator skips the cells switched off by a mask
+    pub masks: bool,
+    /// Whether the generator carves stairs between stacked levels
+    pub levels: bool,
+    pub parameters: &'static [Parameter],
+}
+
+impl Descriptor {
+    pub fn supports(&self, grid: &Grid) -> bool {
+        self.topologies.contains(&grid.topology())
+            && (self.masks || !grid.is_masked())
+            && (self.levels || grid.levels() == 1)
+    }
+
+    /// Runs the generator over the empty grid to the end with the default parameters
+    pub fn generate(&self, grid: Grid, seed: u64) -> Vec<MazeSnapshot> {
+        self.generate_with(grid, seed, &[])
+    }
+
+    /// Runs the generator over the empty grid to the end. The values override the defaults of the parameters with
+    /// their names, clamped to the ranges of the parameters. The same seed always produces the same maze
+    pub fn generate_with(&self, grid: Grid, seed: u64, values: &[(String, f64)]) -> Vec<MazeSnapshot> {
+        let params = self
+            .parameters
+            .iter()
+            .map(|param| match values.iter().rfind(|(name, _)| name == param.name) {
+                Some((_, value)) => value.clamp(param.min, param.max),
+                None => param.default,
+            })
+            .collect::<Vec<_>>();
+
+        (self.factory)(grid, seed, &params)
+    }
+}
+
+/// The factory of a generator without parameters
+pub fn generate<G: IGenerator>(grid: Grid, seed: u64, _: &[f64]) -> Vec<MazeSnapshot> {
+    G::init(grid, seed).run()
+}
+
+const ALL: &[Topology] = &Topology::ALL;
+const SQUARES: &[Topology] = &[Topology::Rectangular];
+
+/// All the built-in generators, in the order of the menu
+pub fn generators() -> Vec<Descriptor> {
+    let descriptor = |name, id, about, factory| Descriptor {
+        name,
+        id,
+        about,
+        factory,
+        topologies: ALL,
+        masks: true,
+        levels: true,
+        parameters: &[],
+    };
+
+    vec![
+        descriptor(
+            "Recursive Backtracker",
+            "recursive-backtracker",
+            About {
+                description: "Walks randomly, carving into unvisited cells, and backs up to the last cell with \
+                              unvisited neighbours when it gets stuck",
+                pseudocode: &[
+                    "push the start cell",
+                    "while the stack isn't empty:",
+                    "  cell ← top of the stack",
+                    "  if cell has unvisited neighbours:",
+                    "    carve into a random one, push it",
+                    "  else: pop cell",
+                ],
+                bias: "Long winding corridors with few dead ends, the highest river factor",
+                time: "O(n)",
+                memory: "O(n) for the stack",
+                highlights: "the stack, from the start to the cell carved last",
+                sets: None,
+            },
+            generate::<RecursiveBacktracking>,
+        ),
+        descriptor(
+            "Prim's (simplified)",
+            "prim",
+            About {
+                description: "Grows the maze from a random cell, joining a random cell next to the maze to a random \
+                              neighbour in it",
+                pseudocode: &[
+                    "add the start cell to the maze",
+                    "frontier ← its neighbours",
+                    "while frontier isn't empty:",
+                    "  take a random cell out of frontier",
+                    "  carve into it from a random neighbour in the maze",
+                    "  add its neighbours outside the maze to frontier",
+                ],
+                bias: "Many short dead ends radiating from the start cell",
+                time: "O(n)",
+                memory: "O(n) for the frontier",
+                highlights: "the frontier, cells next to the maze waiting to join it",
+                sets: None,
+            },
+            generate::<Prim>,
+        ),
+        descriptor(
+            "Prim's (true)",
+            "true-prim",
+            About {
+                description: "Grows the maze from a random cell, giving every passage a random cost and carving the \
+                              cheapest one leading out of the maze",
+                pseudocode: &[
+                    "add the start cell to the maze",
+                    "queue ← its passages with random costs",
+                    "while queue isn't empty:",
+                    "  take the cheapest passage out of queue",
+                    "  if it leads out of the maze:",
+                    "    carve it, add the cell to the maze",
+                    "    queue its passages with random costs",
+                ],
+                bias: "Even more short dead ends than the simplified version, a jigsaw texture",
+                time: "O(n log n) for the priority queue",
+                memory: "O(n) for the queue",
+                highlights: "the frontier, cells next to the maze waiting to join it",
+                sets: None,
+            },
+            generate::<TruePrim>,
+        ),
+        descriptor(
+            "Hunt & Kill",
+            "hunt-and-kill",
+            About {
+                description: "Walks randomly until it gets stuck, then hunts row by row for an unvisited cell next to \
+                              the maze to walk from",
+                pseudocode: &[
+                    "cell ← a random cell",
+                    "loop:",
+                    "  if cell has unvisited neighbours:",
+                    "    carve into a random one, walk there",
+                    "  else if scanning the rows finds an unvisited cell",
+                    "  next to the maze:",
+                    "    carve into the maze from it, walk there",
+                    "  else: stop",
+                ],
+                bias: "Long winding corridors like the backtracker's, with slightly more dead ends",
+                time: "O(n²) in the worst case, every hunt scans the rows",
+                memory: "O(1)",
+                highlights: "the current walk, then the row being hunted",
+                sets: None,
+            },
+            generate::<HuntAndKill>,
+        ),
+        descriptor(
+            "Kruskal's",
+            "kruskal",
+            About {
+                description: "Carves the walls in random order, joining the sets of cells on both sides if they're \
+                              apart",
+                pseudocode: &[
+                    "put every cell in a set of its own",
+                    "for every wall in random order:",
+                    "  if the cells on its sides are in different sets:",
+                    "    carve the wall, merge the sets",
+                ],
+                bias: "Many short dead ends evenly spread, no direction preferred",
+                time: "O(n α(n)) with union-find",
+                memory: "O(n) for the walls and the sets",
+                highlights: "the cells on both sides of the wall carved",
+                sets: Some("cells already joined by passages"),
+            },
+            generate::<Kruskal>,
+        ),
+        Descriptor {
+            // passages can only cross over square cells
+            topologies: SQUARES,
+            parameters: &[Parameter {
+                name: "density",
+                description: "the chance of a cell to get a crossing",
+                default: 0.3,
+                min: 0.0,
+                max: 1.0,
+            }],
+            ..descriptor(
+                "Kruskal's (weave)",
+                "kruskal-weave",
+                About {
+                    description: "Weaves passages over and under each other before running Kruskal's algorithm over \
+                                  the rest",
+                    pseudocode: &[
+                        "put every cell in a set of its own",
+                        "for random cells, by density:",
+                        "  if the cells around are in four sets:",
+                        "    carve a passage over the cell",
+                        "    and a tunnel under it, merge the sets",
+                        "run Kruskal's algorithm over the rest",
+                    ],
+                    bias: "Kruskal's short dead ends, with corridors crossing each other",
+                    time: "O(n α(n)) with union-find",
+                    memory: "O(n) for the walls and the sets",
+                    highlights: "the crossing and the ends of its tunnel, then the cells on both sides of the wall \
+                                 carved",
+                    sets: Some("cells already joined by passages"),
+                },
+                |grid, seed, params| Kruskal::init(grid, seed).with_crossings(params[0]).run(),
+            )
+        },
+        descriptor(
+            "Aldou-Broder's",
+            "aldous-broder",
+            About {
+                description: "Walks randomly all over the grid, carving into every cell it enters for the first time",
+                pseudocode: &[
+                    "cell ← a random cell",
+                    "while there are unvisited cells:",
+                    "  next ← a random neighbour",
+                    "  if next is unvisited: carve into it",
+                    "  cell ← next",
+                ],
+                bias: "None: every maze of the grid is equally likely",
+                time: "O(n log² n) on average on square grids, unbounded in the worst case",
+                memory: "O(1)",
+                highlights: "the walker",
+                sets: None,
+            },
+            generate::<AldousBroder>,
+        ),
+        // Eller's and Sidewinder carve the grid row by row, so they only work with full rows of square cells on a
+        // single level
+        Descriptor {
+            topologies: SQUARES,
+            masks: false,
+            levels: false,
+            ..descriptor(
+                "Eller's",
+                "eller",
+                About {
+                    description: "Carves a row at a time, joining random sets of cells in the row and carrying every \
+                                  set down",
+                    pseudocode: &[
+                        "for every row:",
+                        "  put the cells without a set in sets of their own",
+                        "  join random neighbours of different sets",
+                        "  (all of them in the last row)",
+                        "  for every set, carve down from random cells",
+                    ],
+                    bias: "Horizontal corridors, a texture close to Kruskal's",
+                    time: "O(n)",
+                    memory: "O(width) for the sets of a row",
+                    highlights: "the cells being joined",
+                    sets: Some("cells already joined by passages"),
+                },
+                generate::<Eller>,
+            )
+        },
+        Descriptor {
+            topologies: SQUARES,
+            masks: false,
+            levels: false,
+            ..descriptor(
+                "Sidewinder",
+                "sidewinder",
+                About {
+                    description: "Carves runs of cells eastward a row at a time, and a passage north from a random \
+                                  cell of every run",
+                    pseudocode: &[
+                        "for every row:",
+                        "  for every cell:",
+                        "    add cell to the run",
+                        "    if in the top row or a coin says so:",
+                        "      carve east",
+                        "    else: carve north from a random cell",
+                        "    of the run, start a new run",
+                    ],
+                    bias: "A straight corridor along the top row, the way north never turns south",
+                    time: "O(n)",
+                    memory: "O(1)",
+                    highlights: "nothing, every step carves the next cell of the row",
+                    sets: None,
+                },
+                generate::<Sidewinder>,
+            )
+        },
+    ]
+}
+
+/// Finds a built-in generator by its command line id
+pub fn find(id: &str) -> Option<Descriptor> {
+    generators().into_iter().find(|descriptor| descriptor.id == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::grid::cell::Cell;
+
+    #[test]
+    fn ids_are_unique() {
+        let mut ids = generators().iter().map(|descriptor| descriptor.id).collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+
+        assert_eq!(ids.len(), generators().len());
+        assert_eq!(find("eller").map(|descriptor| descriptor.name), Some("Eller's"));
+        assert!(find("eller's").is_none());
+    }
+
+    #[test]
+    fn every_generator_is_described() {
+        for descriptor in generators() {
+            let about = descriptor.about;
+            let fields = [
+                about.description,
+                about.bias,
+                about.time,
+                about.memory,
+                about.highlights,
+            ];
+
+            assert!(
+                fields.iter().all(|field| !field.is_empty()),
+                "{} is not described",
+                descriptor.name
+            );
+            assert!(!about.pseudocode.is_empty(), "{} has no pseudocode", descriptor.name);
+        }
+    }
+
+    #[test]
+    fn parameters_are_clamped() {
+        let weave = find("kruskal-weave").unwrap();
+        let crossings = |values: &[(String, f64)]| {
+            let snapshots = weave.generate_with(Grid::new(8, 8), 3, values);
+            let grid = snapshots.last().unwrap().get_grid().clone();
+            grid.positions()
+                .filter(|pos| grid.is_cell_carved(*pos, Cell::UNDER))
+                .count()
+        };
+
+        assert_eq!(crossings(&[(String::from("density"), 0.0)]), 0);
+        assert_eq!(
+            crossings(&[(String::from("density"), -4.0)]),
+            crossings(&[(String::from("density"), 0.0)])
+        );
+        assert!(crossings(&[]) > 0);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::app::{state::MazeSnapshot, grid::cell::Cell};
+use crate::app::{
+    state::MazeSnapshot,
+    grid::{cell::Cell, Grid},
+};
 use rand::prelude::*;
 
 use super::{Generator, IGenerator, Snapshot};
@@ -9,19 +12,20 @@ pub struct Sidewinder {
 
 impl Sidewinder {
     fn generate(&mut self) {
-        let mut rng = rand::thread_rng();
-
         for y in 0..self.generator.grid.height() {
             let mut run_start = 0;
 
             for x in 0..self.generator.grid.width() {
-                let carve_east: bool = rng.gen();
+                let carve_east: bool = self.generator.rng.gen();
 
-                if y == 0 || (carve_east && x + 1 < self.generator.grid.width()) {
+                // runs never cross the edges, even if the grid wraps around
+                if x + 1 < self.generator.grid.width() && (y == 0 || carve_east) {
                     self.generator.grid.carve_passage((x, y), Cell::EAST).ok();
                 } else {
-                    let rand_x = rng.gen_range(run_start..=x);
-                    self.generator.grid.carve_passage((rand_x, y), Cell::NORTH).ok();
+                    let rand_x = self.generator.rng.gen_range(run_start..=x);
+                    if y > 0 {
+                        self.generator.grid.carve_passage((rand_x, y), Cell::NORTH).ok();
+                    }
                     run_start = x + 1;
                 }
 
@@ -32,9 +36,9 @@ impl Sidewinder {
 }
 
 impl IGenerator for Sidewinder {
-    fn init(width: usize, height: usize) -> Self {
+    fn init(grid: Grid, seed: u64) -> Self {
         Self {
-            generator: Generator::new(width, height),
+            generator: Generator::new(grid, seed),
         }
     }
 
@@ -0,0 +1,84 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::app::{
+    grid::Grid,
+    state::MazeSnapshot,
+    utils::{random::get_start_pos, types::Pos},
+};
+use rand::prelude::*;
+
+use super::{prim::Frontiers, Generator, IGenerator, Snapshot};
+
+/// A passage that can be carved from a cell of the maze to a cell outside of it
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct Edge {
+    weight: u32,
+    to: Pos,
+    from: Pos,
+}
+
+/// True Prim's: every passage gets a random weight and the cheapest one leading out of the maze is carved next
+pub struct TruePrim {
+    generator: Generator,
+    edges: BinaryHeap<Reverse<Edge>>,
+    /// The cells the edges lead to, highlighted at every step
+    frontiers: Frontiers,
+}
+
+impl TruePrim {
+    /// Adds the edges to the neighbours outside of the maze. Every edge is added once: when the first of its cells
+    /// joins the maze
+    fn mark(&mut self, pos: Pos) {
+        self.generator.grid.mark_cell(pos);
+        self.frontiers.remove(pos);
+
+        for to in self.generator.grid.neighbours(pos) {
+            if !self.generator.grid.is_cell_marked(to) {
+                let weight = self.generator.rng.gen();
+                self.edges.push(Reverse(Edge { weight, to, from: pos }));
+                if !self.frontiers.contains(to) {
+                    self.frontiers.push(to);
+                }
+            }
+        }
+    }
+
+    fn highlight_frontiers(&mut self) {
+        self.generator.highlights = self.frontiers.cells.clone();
+    }
+}
+
+impl IGenerator for TruePrim {
+    fn init(grid: Grid, seed: u64) -> Self {
+        let frontiers = Frontiers::new(grid.width(), grid.height());
+        let generator = Generator::new(grid, seed);
+        Self {
+            generator,
+            edges: BinaryHeap::new(),
+            frontiers,
+        }
+    }
+
+    fn run(&mut self) -> Vec<MazeSnapshot> {
+        let start_pos = get_start_pos(&mut self.generator.rng, &self.generator.grid);
+        self.mark(start_pos);
+
+        self.highlight_frontiers();
+        self.generator.make_snapshot();
+
+        while let Some(Reverse(edge)) = self.edges.pop() {
+            // the cell has joined the maze through a cheaper edge
+            if self.generator.grid.is_cell_marked(edge.to) {
+                continue;
+            }
+
+            self.generator.grid.link(edge.to, edge.from).unwrap();
+            self.mark(edge.to);
+
+            self.highlight_frontiers();
+            self.generator.make_snapshot();
+        }
+
+        self.generator.get_snapshots()
+    }
+}
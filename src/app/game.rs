@@ -0,0 +1,438 @@
+use std::{
+    collections::VecDeque,
+    fmt,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use super::{
+    grid::{cell::Cell, topology::Topology, Grid},
+    stats::distances,
+    utils::types::Pos,
+};
+
+/// How far the player sees in a maze covered by fog
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Fog {
+    /// Along the straight passages leading from the player's cell until they hit a wall
+    #[default]
+    LineOfSight,
+    /// Cells up to the number of moves away
+    Steps(usize),
+}
+
+impl FromStr for Fog {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "sight" | "line-of-sight" => Ok(Fog::LineOfSight),
+            _ => s
+                .parse()
+                .map(Fog::Steps)
+                .map_err(|_| format!("Unknown fog '{}'. Expected sight or a number of steps", s)),
+        }
+    }
+}
+
+impl fmt::Display for Fog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Fog::LineOfSight => write!(f, "line of sight"),
+            Fog::Steps(steps) => write!(f, "{} steps", steps),
+        }
+    }
+}
+
+/// How a cell is shown in a maze covered by fog
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Visibility {
+    /// Never seen, not rendered at all
+    #[default]
+    Hidden,
+    /// Seen before, rendered dimmed
+    Seen,
+    /// In sight of the player
+    Visible,
+}
+
+/// The max number of players racing in one maze
+pub const MAX_PLAYERS: usize = 2;
+
+/// Players walking a finished maze from the entrance in the first cell to the exit in the last one. With more than
+/// one player it's a race won by the first to reach the exit
+pub struct Game {
+    grid: Grid,
+    exit: Pos,
+    players: Vec<Player>,
+    /// The number of moves on the shortest path from the entrance to the exit
+    optimal: usize,
+    started: Instant,
+    /// The time the first player reached the exit, which ends the game
+    finished: Option<Duration>,
+    /// Hides the cells out of the players' sight. Without fog the whole maze is shown
+    fog: Option<Fog>,
+    visibility: Vec<Visibility>,
+}
+
+struct Player {
+    pos: Pos,
+    /// Cells the player has been in, indexed as `y * width + x`
+    trail: Vec<bool>,
+    moves: usize,
+}
+
+/// The part of the game on a level of the grid, with positions relative to the level
+pub struct GameView {
+    /// Positions of the players on the level, in the order of the players
+    pub players: Vec<Option<Pos>>,
+    pub exit: Option<Pos>,
+    /// Cells every player has been in
+    pub trails: Vec<Vec<bool>>,
+    /// Visibility of every cell in the fog. Empty if there's no fog
+    pub visibility: Vec<Visibility>,
+}
+
+impl Game {
+    /// Starts a game in the maze for a single player. Hexagons and rings have no left and right neighbours to move to
+    /// with the arrow keys, and a single cell is both the entrance and the exit, leaving nothing to find
+    pub fn new(grid: Grid) -> Option<Game> {
+        if !Game::supports(&grid) {
+            return None;
+        }
+
+        let entrance = grid.positions().next()?;
+        let exit = grid.positions().last()?;
+        if entrance == exit {
+            return None;
+        }
+        let optimal = distances(&grid, entrance)[exit.1 * grid.width() + exit.0]?;
+
+        let mut game = Game {
+            grid,
+            exit,
+            players: vec![],
+            optimal,
+            started: Instant::now(),
+            finished: None,
+            fog: None,
+            visibility: vec![],
+        };
+        game.players = vec![game.new_player(entrance)];
+        Some(game)
+    }
+
+    /// Lets the players race from the same entrance
+    pub fn with_players(mut self, count: usize) -> Self {
+        let entrance = self.players[0].pos;
+        self.players = (0..count.clamp(1, MAX_PLAYERS))
+            .map(|_| self.new_player(entrance))
+            .collect();
+        self.look_around();
+        self
+    }
+
+    /// Covers the maze with fog, so only the cells in the players' sight are shown
+    pub fn with_fog(mut self, fog: Fog) -> Self {
+        self.fog = Some(fog);
+        self.visibility = vec![Visibility::Hidden; self.grid.width() * self.grid.height()];
+        self.look_around();
+        self
+    }
+
+    pub fn supports(grid: &Grid) -> bool {
+        matches!(grid.topology(), Topology::Rectangular | Topology::Triangular)
+    }
+
+    fn new_player(&self, pos: Pos) -> Player {
+        let mut trail = vec![false; self.grid.width() * self.grid.height()];
+        trail[pos.1 * self.grid.width() + pos.0] = true;
+        Player { pos, trail, moves: 0 }
+    }
+
+    /// Moves the player through the passage in the direction. Walls, and every move after the exit is reached, leave
+    /// the player where it is
+    pub fn step(&mut self, player: usize, direction: Cell) -> bool {
+        let pos = match self.players.get(player) {
+            Some(player) if !self.is_won() => player.pos,
+            _ => return false,
+        };
+        if !self.grid.is_cell_carved(pos, direction) {
+            return false;
+        }
+
+        let next = match self.grid.get_next_cell_pos(pos, direction) {
+            Ok(next) => next,
+            Err(_) => return false,
+        };
+
+        let width = self.grid.width();
+        let player = &mut self.players[player];
+        player.pos = next;
+        player.moves += 1;
+        player.trail[next.1 * width + next.0] = true;
+        if next == self.exit {
+            self.finished = Some(self.started.elapsed());
+        }
+        self.look_around();
+        true
+    }
+
+    pub fn players(&self) -> usize {
+        self.players.len()
+    }
+
+    pub fn moves(&self, player: usize) -> usize {
+        self.players.get(player).map_or(0, |player| player.moves)
+    }
+
+    pub fn optimal(&self) -> usize {
+        self.optimal
+    }
+
+    pub fn fog(&self) -> Option<Fog> {
+        self.fog
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.finished.is_some()
+    }
+
+    /// The player who reached the exit first
+    pub fn winner(&self) -> Option<usize> {
+        self.players.iter().position(|player| player.pos == self.exit)
+    }
+
+    /// Time since the start, frozen once a player reaches the exit
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(|| self.started.elapsed())
+    }
+
+    /// The level of a stacked grid the player is on
+    pub fn player_level(&self, player: usize) -> usize {
+        self.players
+            .get(player)
+            .map_or(0, |player| player.pos.1 / self.grid.level_height())
+    }
+
+    /// Dims the cells that were visible before and reveals the ones in the players' sight now
+    fn look_around(&mut self) {
+        let fog = match self.fog {
+            Some(fog) => fog,
+            None => return,
+        };
+
+        for visibility in self.visibility.iter_mut() {
+            if *visibility == Visibility::Visible {
+                *visibility = Visibility::Seen;
+            }
+        }
+
+        let in_sight = self
+            .players
+            .iter()
+            .flat_map(|player| match fog {
+                Fog::LineOfSight => self.line_of_sight(player.pos),
+                Fog::Steps(steps) => self.within_steps(player.pos, steps),
+            })
+            .collect::<Vec<_>>();
+        for (x, y) in in_sight {
+            self.visibility[y * self.grid.width() + x] = Visibility::Visible;
+        }
+    }
+
+    /// Follows every passage leading from the cell for as long as it goes straight
+    fn line_of_sight(&self, from: Pos) -> Vec<Pos> {
+        let mut in_sight = vec![from];
+
+        for &direction in self.grid.topology().directions() {
+            let mut pos = from;
+            while self.grid.is_cell_carved(pos, direction) {
+                match self.grid.get_next_cell_pos(pos, direction) {
+                    Ok(next) if !in_sight.contains(&next) => {
+                        in_sight.push(next);
+                        pos = next;
+                    }
+                    _ => break,
+                }
+            }
+        }
+
+        in_sight
+    }
+
+    /// Walks the passages from the cell up to the number of moves
+    fn within_steps(&self, from: Pos, steps: usize) -> Vec<Pos> {
+        let mut in_sight = vec![from];
+        let mut queue = VecDeque::from([(from, 0)]);
+
+        while let Some((pos, distance)) = queue.pop_front() {
+            if distance == steps {
+                continue;
+            }
+            for next in self.grid.links(pos) {
+                if !in_sight.contains(&next) {
+                    in_sight.push(next);
+                    queue.push_back((next, distance + 1));
+                }
+            }
+        }
+
+        in_sight
+    }
+
+    /// Cuts the players, the exit and the trails on the level out of the game
+    pub fn view(&self, level: usize) -> GameView {
+        let level_height = self.grid.level_height();
+        let rows = level * level_height..(level + 1) * level_height;
+        let on_level = |(x, y): Pos| rows.contains(&y).then(|| (x, y - rows.start));
+        let cells = rows.start * self.grid.width()..rows.end * self.grid.width();
+
+        GameView {
+            players: self.players.iter().map(|player| on_level(player.pos)).collect(),
+            exit: on_level(self.exit),
+            trails: self
+                .players
+                .iter()
+                .map(|player| {
+                    player
+                        .trail
+                        .get(cells.clone())
+                        .map(|trail| trail.to_vec())
+                        .unwrap_or_default()
+                })
+                .collect(),
+            visibility: self
+                .visibility
+                .get(cells)
+                .map(|visibility| visibility.to_vec())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Carves a 3x2 maze:
+    ///  _____
+    /// |_  | |
+    /// |_____|
+    fn carve_grid() -> Grid {
+        let mut grid = Grid::new(3, 2);
+        grid.carve_passage((0, 0), Cell::EAST).unwrap();
+        grid.carve_passage((1, 0), Cell::SOUTH).unwrap();
+        grid.carve_passage((0, 1), Cell::EAST).unwrap();
+        grid.carve_passage((1, 1), Cell::EAST).unwrap();
+        grid.carve_passage((2, 1), Cell::NORTH).unwrap();
+        grid
+    }
+
+    #[test]
+    fn walls_block_the_player() {
+        let mut game = Game::new(carve_grid()).unwrap();
+
+        assert!(!game.step(0, Cell::SOUTH) && !game.step(0, Cell::WEST));
+        assert_eq!((game.view(0).players[0], game.moves(0)), (Some((0, 0)), 0));
+        assert!(game.step(0, Cell::EAST));
+        assert_eq!((game.view(0).players[0], game.moves(0)), (Some((1, 0)), 1));
+    }
+
+    #[test]
+    fn single_cell_cannot_be_played() {
+        assert!(Game::new(Grid::new(1, 1)).is_none());
+
+        let mut grid = Grid::new(2, 1);
+        grid.carve_passage((0, 0), Cell::EAST).unwrap();
+        let mut game = Game::new(grid).unwrap();
+        assert!(!game.is_won());
+        assert!(game.step(0, Cell::EAST) && game.is_won());
+    }
+
+    #[test]
+    fn reaching_the_exit_wins() {
+        let mut game = Game::new(carve_grid()).unwrap();
+        assert_eq!(game.optimal(), 3);
+
+        for direction in [Cell::EAST, Cell::SOUTH, Cell::WEST, Cell::EAST, Cell::EAST] {
+            assert!(!game.is_won());
+            assert!(game.step(0, direction));
+        }
+
+        assert!(game.is_won());
+        assert_eq!((game.view(0).players[0], game.moves(0)), (Some((2, 1)), 5));
+        assert!(!game.step(0, Cell::NORTH));
+        assert_eq!(game.view(0).trails[0], vec![true, true, false, true, true, true]);
+    }
+
+    #[test]
+    fn stacked_game_is_viewed_by_level() {
+        let mut grid = Grid::new(2, 1).stacked(2);
+        grid.carve_passage((0, 0), Cell::UP).unwrap();
+        grid.carve_passage((0, 1), Cell::EAST).unwrap();
+        let mut game = Game::new(grid).unwrap();
+
+        assert_eq!(game.view(0).exit, None);
+        assert!(game.step(0, Cell::UP));
+        assert_eq!(game.player_level(0), 1);
+
+        let view = game.view(1);
+        assert_eq!((view.players, view.exit), (vec![Some((0, 0))], Some((1, 0))));
+        assert_eq!(view.trails, vec![vec![true, false]]);
+    }
+
+    #[test]
+    fn first_player_to_reach_the_exit_wins_the_race() {
+        let mut game = Game::new(carve_grid()).unwrap().with_players(2);
+        assert_eq!(game.players(), 2);
+
+        assert!(game.step(0, Cell::EAST) && game.step(1, Cell::EAST) && game.step(1, Cell::SOUTH));
+        assert!(game.step(0, Cell::SOUTH) && game.step(1, Cell::EAST));
+        assert_eq!((game.winner(), game.moves(0), game.moves(1)), (Some(1), 2, 3));
+        // the race is over once the exit is reached
+        assert!(!game.step(0, Cell::EAST));
+
+        let view = game.view(0);
+        assert_eq!(view.players, vec![Some((1, 1)), Some((2, 1))]);
+        assert_eq!(view.trails[0], vec![true, true, false, false, true, false]);
+    }
+
+    #[test]
+    fn fog_reveals_straight_passages() {
+        let mut game = Game::new(carve_grid()).unwrap().with_fog(Fog::LineOfSight);
+        let visibility = |game: &Game| game.view(0).visibility;
+        let (hidden, seen, visible) = (Visibility::Hidden, Visibility::Seen, Visibility::Visible);
+
+        assert_eq!(
+            visibility(&game),
+            vec![visible, visible, hidden, hidden, hidden, hidden]
+        );
+        assert!(game.step(0, Cell::EAST) && game.step(0, Cell::SOUTH));
+        assert_eq!(
+            visibility(&game),
+            vec![seen, visible, hidden, visible, visible, visible]
+        );
+    }
+
+    #[test]
+    fn fog_reveals_cells_within_steps() {
+        let game = Game::new(carve_grid()).unwrap().with_fog(Fog::Steps(2));
+
+        assert_eq!(
+            game.view(0).visibility,
+            [true, true, false, false, true, false].map(|visible| match visible {
+                true => Visibility::Visible,
+                false => Visibility::Hidden,
+            })
+        );
+        assert!(Game::new(carve_grid()).unwrap().view(0).visibility.is_empty());
+        assert_eq!("3".parse::<Fog>(), Ok(Fog::Steps(3)));
+        assert!("far".parse::<Fog>().is_err());
+    }
+
+    #[test]
+    fn hexagons_cannot_be_played() {
+        assert!(Game::new(Grid::with_topology(3, 3, Topology::Hexagonal)).is_none());
+    }
+}
@@ -2,11 +2,38 @@ use bitflags::bitflags;
 
 bitflags! {
     #[derive(Default)]
-    pub struct Cell: u8 {
+    pub struct Cell: u16 {
         const NORTH = 0b0001;
         const SOUTH = 0b0010;
         const EAST =  0b0100;
         const WEST =  0b1000;
+        const NORTH_EAST = 0b0001_0000;
+        const NORTH_WEST = 0b0010_0000;
+        const SOUTH_EAST = 0b0100_0000;
+        const SOUTH_WEST = 0b1000_0000;
+        const UP = 0b0001_0000_0000;
+        const DOWN = 0b0010_0000_0000;
+        /// A passage runs under the cell, across the one carved in it
+        const UNDER = 0b0100_0000_0000;
+    }
+}
+
+impl Cell {
+    /// The direction pointing back, e.g. `SOUTH` for `NORTH`
+    pub fn opposite(self) -> Cell {
+        match self {
+            Cell::NORTH => Cell::SOUTH,
+            Cell::SOUTH => Cell::NORTH,
+            Cell::WEST => Cell::EAST,
+            Cell::EAST => Cell::WEST,
+            Cell::NORTH_EAST => Cell::SOUTH_WEST,
+            Cell::SOUTH_WEST => Cell::NORTH_EAST,
+            Cell::NORTH_WEST => Cell::SOUTH_EAST,
+            Cell::SOUTH_EAST => Cell::NORTH_WEST,
+            Cell::UP => Cell::DOWN,
+            Cell::DOWN => Cell::UP,
+            _ => self,
+        }
     }
 }
 
@@ -0,0 +1,255 @@
+use std::{fmt, fs, path::Path};
+
+use super::topology::Topology;
+use crate::app::utils::types::Pos;
+
+/// Cells of a grid that are switched on or off. A masked grid only has the cells that are on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mask {
+    width: usize,
+    height: usize,
+    cells: Vec<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaskError {
+    /// The mask file cannot be read
+    Io(String),
+    /// The image is not a monochrome PBM image
+    InvalidImage(String),
+    /// No cell is switched on
+    Empty,
+    /// A cell cannot be reached from the first one, so no maze can connect them
+    Disconnected { pos: Pos },
+    /// Masks only switch off cells of grids with rows of the same width
+    UnsupportedTopology(Topology),
+}
+
+impl fmt::Display for MaskError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MaskError::Io(reason) => write!(f, "Cannot read the mask. Reason: {}", reason),
+            MaskError::InvalidImage(reason) => write!(f, "Invalid PBM image. Reason: {}", reason),
+            MaskError::Empty => write!(f, "The mask has no cells"),
+            MaskError::Disconnected { pos: (x, y) } => {
+                write!(f, "The mask falls apart, cell is cut off. Pos: x = {}, y = {}", x, y)
+            }
+            MaskError::UnsupportedTopology(topology) => write!(f, "Masks cannot be applied to a {} grid", topology),
+        }
+    }
+}
+
+type MaskResult = Result<Mask, MaskError>;
+
+impl Mask {
+    /// Loads a PBM image if the file has the `pbm` extension, otherwise a text template
+    pub fn load(path: &Path) -> MaskResult {
+        let bytes = fs::read(path).map_err(|err| MaskError::Io(err.to_string()))?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("pbm") => Mask::from_pbm(&bytes),
+            _ => Mask::from_ascii(&String::from_utf8_lossy(&bytes)),
+        }
+    }
+
+    /// Reads a text template where every character is a cell. Dots and spaces are switched off, anything else is on.
+    /// Shorter lines are padded with cells that are off
+    pub fn from_ascii(text: &str) -> MaskResult {
+        let lines = text
+            .lines()
+            .map(|line| line.trim_end())
+            .skip_while(|line| line.is_empty())
+            .collect::<Vec<_>>();
+        let height = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |idx| idx + 1);
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+
+        let mut cells = vec![false; width * height];
+        for (y, line) in lines.iter().take(height).enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                cells[y * width + x] = c != '.' && !c.is_whitespace();
+            }
+        }
+
+        Mask::new(width, height, cells)
+    }
+
+    /// Reads a plain (`P1`) or raw (`P4`) PBM image, black pixels are cells
+    pub fn from_pbm(bytes: &[u8]) -> MaskResult {
+        let mut reader = PbmReader { bytes, pos: 0 };
+        let magic = reader.token()?;
+        let width = reader.number()?;
+        let height = reader.number()?;
+
+        let cells = match magic.as_str() {
+            "P1" => {
+                let mut cells = Vec::with_capacity(width * height);
+                while cells.len() < width * height {
+                    match reader.next_byte()? {
+                        b'0' => cells.push(false),
+                        b'1' => cells.push(true),
+                        b'#' => reader.skip_comment(),
+                        byte if byte.is_ascii_whitespace() => (),
+                        byte => return Err(invalid(format!("Unexpected pixel '{}'", byte as char))),
+                    }
+                }
+                cells
+            }
+            "P4" => {
+                // a single whitespace separates the header from the pixels
+                reader.next_byte()?;
+                let row_len = width.div_ceil(8);
+                let mut cells = Vec::with_capacity(width * height);
+                for _ in 0..height {
+                    let row = reader.take(row_len)?;
+                    cells.extend((0..width).map(|x| row[x / 8] & (0x80 >> (x % 8)) != 0));
+                }
+                cells
+            }
+            _ => return Err(invalid(format!("Unsupported format '{}'. Expected P1 or P4", magic))),
+        };
+
+        Mask::new(width, height, cells)
+    }
+
+    fn new(width: usize, height: usize, cells: Vec<bool>) -> MaskResult {
+        if !cells.contains(&true) {
+            return Err(MaskError::Empty);
+        }
+        Ok(Mask { width, height, cells })
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_on(&self, (x, y): Pos) -> bool {
+        x < self.width && y < self.height && self.cells[y * self.width + x]
+    }
+}
+
+fn invalid(reason: String) -> MaskError {
+    MaskError::InvalidImage(reason)
+}
+
+struct PbmReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PbmReader<'a> {
+    fn next_byte(&mut self) -> Result<u8, MaskError> {
+        let byte = *self
+            .bytes
+            .get(self.pos)
+            .ok_or_else(|| invalid(String::from("Unexpected end of the image")))?;
+        self.pos += 1;
+        Ok(byte)
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], MaskError> {
+        let bytes = self
+            .bytes
+            .get(self.pos..self.pos + count)
+            .ok_or_else(|| invalid(String::from("Unexpected end of the image")))?;
+        self.pos += count;
+        Ok(bytes)
+    }
+
+    fn skip_comment(&mut self) {
+        while self.pos < self.bytes.len() && self.bytes[self.pos] != b'\n' {
+            self.pos += 1;
+        }
+    }
+
+    /// Reads a header token skipping whitespace and comments
+    fn token(&mut self) -> Result<String, MaskError> {
+        loop {
+            match self.bytes.get(self.pos) {
+                Some(b'#') => self.skip_comment(),
+                Some(byte) if byte.is_ascii_whitespace() => self.pos += 1,
+                _ => break,
+            }
+        }
+
+        let start = self.pos;
+        while self.pos < self.bytes.len() && !self.bytes[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+
+        match start == self.pos {
+            true => Err(invalid(String::from("Unexpected end of the header"))),
+            false => Ok(String::from_utf8_lossy(&self.bytes[start..self.pos]).to_string()),
+        }
+    }
+
+    fn number(&mut self) -> Result<usize, MaskError> {
+        let token = self.token()?;
+        token
+            .parse()
+            .map_err(|_| invalid(format!("Expected a number, found '{}'", token)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::grid::Grid;
+
+    #[test]
+    fn ascii_template() {
+        let mask = Mask::from_ascii("\n#.#\n ##\n\n").unwrap();
+
+        assert_eq!((mask.width(), mask.height()), (3, 2));
+        assert!(mask.is_on((0, 0)) && mask.is_on((2, 0)) && mask.is_on((1, 1)));
+        assert!(!mask.is_on((1, 0)) && !mask.is_on((0, 1)));
+        assert!(!mask.is_on((3, 0)));
+    }
+
+    #[test]
+    fn plain_pbm() {
+        let mask = Mask::from_pbm(b"P1\n# a comment\n3 2\n1 0 1\n011\n").unwrap();
+        assert_eq!(mask, Mask::from_ascii("#.#\n.##").unwrap());
+    }
+
+    #[test]
+    fn raw_pbm() {
+        let mask =
+            Mask::from_pbm(&[b"P4 10 2\n".as_slice(), &[0b1000_0000, 0b0100_0000, 0, 0b1100_0000]].concat()).unwrap();
+
+        assert_eq!((mask.width(), mask.height()), (10, 2));
+        assert!(mask.is_on((0, 0)) && mask.is_on((9, 0)) && mask.is_on((8, 1)) && mask.is_on((9, 1)));
+        assert!(!mask.is_on((1, 0)) && !mask.is_on((0, 1)));
+    }
+
+    #[test]
+    fn invalid_masks() {
+        assert_eq!(Mask::from_ascii("...\n. ."), Err(MaskError::Empty));
+        assert!(matches!(Mask::from_pbm(b"P2 1 1 1"), Err(MaskError::InvalidImage(_))));
+        assert!(matches!(
+            Mask::from_pbm(b"P1 2 2 1 1 1"),
+            Err(MaskError::InvalidImage(_))
+        ));
+    }
+
+    #[test]
+    fn masked_grid_must_be_connected() {
+        let mask = Mask::from_ascii("#.#\n#.#").unwrap();
+
+        assert_eq!(
+            Grid::with_mask(&mask, Topology::Rectangular).err(),
+            Some(MaskError::Disconnected { pos: (2, 0) })
+        );
+        assert_eq!(
+            Grid::with_mask(&mask, Topology::Polar).err(),
+            Some(MaskError::UnsupportedTopology(Topology::Polar))
+        );
+
+        let grid = Grid::with_mask(&Mask::from_ascii("##\n#.").unwrap(), Topology::Rectangular).unwrap();
+        assert_eq!(grid.cells_count(), 3);
+        assert!(grid.is_masked() && !grid.contains((1, 1)));
+    }
+}
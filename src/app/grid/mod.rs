@@ -1,8 +1,17 @@
 pub mod cell;
+pub mod mask;
+pub mod topology;
+pub mod validation;
+pub mod weights;
+pub mod wrap;
 
-use super::utils::types::Pos;
+use super::utils::{shared_vec::SharedVec, types::Pos};
 use cell::{Cell, CellStatus};
-use std::fmt;
+use mask::{Mask, MaskError};
+use std::{collections::VecDeque, fmt, sync::Arc};
+use topology::Topology;
+use weights::{Weights, MIN_WEIGHT};
+use wrap::Wrap;
 
 #[derive(Debug, Clone)]
 pub struct TransitError {
@@ -24,31 +33,205 @@ impl fmt::Display for TransitError {
 
 type TransitResult<T> = Result<T, TransitError>;
 
+/// Clones of a grid share its cells until they're carved, so every snapshot of a maze only costs what changed since
+/// the previous one
 pub struct Grid {
+    /// The number of cells in the widest row
     width: usize,
+    /// The number of rows in all the levels
     height: usize,
-    cells: Vec<Cell>,
-    cell_statuses: Vec<CellStatus>,
+    /// Levels are stacked as rows below the first one, so positions stay two-dimensional
+    levels: usize,
+    level_height: usize,
+    topology: Topology,
+    wrap: Wrap,
+    row_widths: Arc<[usize]>,
+    /// Cells switched off by a mask are not part of the grid
+    active: Arc<[bool]>,
+    /// Costs of walking through the cells. Empty if every cell costs the same
+    weights: Arc<[usize]>,
+    cells: SharedVec<Cell>,
+    cell_statuses: SharedVec<CellStatus>,
 }
 
 impl Grid {
     pub fn new(width: usize, height: usize) -> Grid {
+        Grid::with_topology(width, height, Topology::Rectangular)
+    }
+
+    pub fn with_topology(width: usize, height: usize, topology: Topology) -> Grid {
+        let row_widths = topology.row_widths(width, height);
+        let width = row_widths.iter().copied().max().unwrap_or(0);
+
         Grid {
             width,
             height,
-            cells: vec![Cell::default(); width * height],
-            cell_statuses: vec![CellStatus::default(); width * height],
+            levels: 1,
+            level_height: height,
+            topology,
+            wrap: Wrap::None,
+            row_widths: row_widths.into(),
+            active: vec![true; width * height].into(),
+            weights: Arc::new([]),
+            cells: SharedVec::from_elem(Cell::default(), width * height),
+            cell_statuses: SharedVec::from_elem(CellStatus::default(), width * height),
+        }
+    }
+
+    /// Creates a grid of the mask size with only the cells the mask switches on. Every cell must be reachable from
+    /// the others, otherwise no maze can be carved
+    pub fn with_mask(mask: &Mask, topology: Topology) -> Result<Grid, MaskError> {
+        if topology == Topology::Polar {
+            return Err(MaskError::UnsupportedTopology(topology));
         }
+
+        let mut grid = Grid::with_topology(mask.width(), mask.height(), topology);
+        let width = grid.width;
+        grid.active = (0..width * mask.height())
+            .map(|idx| mask.is_on((idx % width, idx / width)))
+            .collect();
+
+        let start = grid.positions().next().ok_or(MaskError::Empty)?;
+        let mut seen = vec![false; width * grid.height];
+        let mut queue = VecDeque::from([start]);
+        seen[start.1 * width + start.0] = true;
+
+        while let Some(pos) = queue.pop_front() {
+            for (nx, ny) in grid.neighbours(pos) {
+                if !seen[ny * width + nx] {
+                    seen[ny * width + nx] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        let cut_off = grid.positions().find(|(x, y)| !seen[y * width + x]);
+        match cut_off {
+            Some(pos) => Err(MaskError::Disconnected { pos }),
+            None => Ok(grid),
+        }
+    }
+
+    /// Stacks copies of an empty grid on top of each other, cells can have passages to the cells right above and below.
+    /// Rings of polar grids cannot be stacked
+    pub fn stacked(mut self, levels: usize) -> Grid {
+        if self.topology == Topology::Polar || levels <= 1 {
+            return self;
+        }
+
+        self.levels = levels;
+        self.height = self.level_height * levels;
+        self.row_widths = self.row_widths.repeat(levels).into();
+        self.active = self.active.repeat(levels).into();
+        self.weights = self.weights.repeat(levels).into();
+        self.cells = SharedVec::from_elem(Cell::default(), self.width * self.height);
+        self.cell_statuses = SharedVec::from_elem(CellStatus::default(), self.width * self.height);
+        self
+    }
+
+    /// Copies a level of a stacked grid into a grid of its own. Passages up and down stay in the cells
+    pub fn level(&self, level: usize) -> Grid {
+        let level = level.min(self.levels - 1);
+        let rows = level * self.level_height..(level + 1) * self.level_height;
+        let idxs = rows.start * self.width..rows.end * self.width;
+
+        Grid {
+            width: self.width,
+            height: self.level_height,
+            levels: 1,
+            level_height: self.level_height,
+            topology: self.topology,
+            wrap: self.wrap,
+            row_widths: self.row_widths[rows].into(),
+            active: self.active[idxs.clone()].into(),
+            weights: self.weights.get(idxs.clone()).unwrap_or_default().into(),
+            cells: self.cells.slice(idxs.clone()).unwrap_or_default(),
+            cell_statuses: self.cell_statuses.slice(idxs).unwrap_or_default(),
+        }
+    }
+
+    /// Makes the edges of the grid lead to the opposite ones. An edge only wraps if the cells on both sides line up:
+    /// hexagons and triangles need an even number of columns, triangles an even number of rows, and there must be at
+    /// least three of them. Polar rings already wrap around and never wrap inwards
+    pub fn wrapped(mut self, wrap: Wrap) -> Grid {
+        let alternates_columns = matches!(self.topology, Topology::Hexagonal | Topology::Triangular);
+        let horizontal =
+            self.topology != Topology::Polar && self.width > 2 && (!alternates_columns || self.width.is_multiple_of(2));
+        let vertical = self.topology != Topology::Polar
+            && self.level_height > 2
+            && (self.topology != Topology::Triangular || self.level_height.is_multiple_of(2));
+
+        self.wrap = Wrap::new(wrap.horizontal() && horizontal, wrap.vertical() && vertical);
+        self
+    }
+
+    /// Gives the cells costs of walking through them. Every level gets the same costs
+    pub fn weighted(mut self, weights: &Weights) -> Grid {
+        let width = self.width;
+        self.weights = (0..width * self.height)
+            .map(|idx| weights.cost((idx % width, idx / width % self.level_height)))
+            .collect();
+        self
+    }
+
+    /// The cost of walking through the cell
+    pub fn weight(&self, (x, y): Pos) -> usize {
+        self.weights.get(y * self.width + x).copied().unwrap_or(MIN_WEIGHT)
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    pub fn wrap(&self) -> Wrap {
+        self.wrap
     }
 
     pub fn height(&self) -> usize {
         self.height
     }
 
+    pub fn levels(&self) -> usize {
+        self.levels
+    }
+
+    /// The number of rows in a level
+    pub fn level_height(&self) -> usize {
+        self.level_height
+    }
+
     pub fn width(&self) -> usize {
         self.width
     }
 
+    /// The number of cells in the row. Rows narrower than the grid have no cells on the right
+    pub fn row_width(&self, y: usize) -> usize {
+        self.row_widths.get(y).copied().unwrap_or(0)
+    }
+
+    /// Checks if the position belongs to a cell of the grid
+    pub fn contains(&self, (x, y): Pos) -> bool {
+        x < self.row_width(y) && self.active[y * self.width + x]
+    }
+
+    /// Checks if a mask switched off some of the cells
+    pub fn is_masked(&self) -> bool {
+        self.active.contains(&false)
+    }
+
+    /// Positions of all the cells row by row
+    pub fn positions(&self) -> impl Iterator<Item = Pos> + '_ {
+        (0..self.height).flat_map(move |y| {
+            (0..self.row_width(y))
+                .map(move |x| (x, y))
+                .filter(move |pos| self.contains(*pos))
+        })
+    }
+
+    pub fn cells_count(&self) -> usize {
+        self.positions().count()
+    }
+
     pub fn mark_cell(&mut self, pos: Pos) {
         self.get_cell_status_mut(pos).mark()
     }
@@ -69,28 +252,77 @@ impl Grid {
         self.get_cell(pos).contains(direction)
     }
 
+    /// Directions the cell can have passages in under the grid topology, including the ones leading out of the grid.
+    /// Cells of stacked grids can also go up and down
+    pub fn directions(&self, pos: Pos) -> Vec<Cell> {
+        let (x, y) = pos;
+        let directions = self.topology.directions().iter().copied();
+
+        let mut directions: Vec<Cell> = match self.topology {
+            // a cell has a second outward neighbour only if the next ring splits it in two
+            Topology::Polar => directions
+                .filter(|dir| *dir != Cell::SOUTH_EAST || self.ring_ratio(y + 1) == 2)
+                .collect(),
+            Topology::Triangular => directions
+                .filter(|dir| self.topology.offset((x, y % self.level_height), *dir).is_some())
+                .collect(),
+            _ => directions.collect(),
+        };
+
+        if self.levels > 1 {
+            directions.extend([Cell::UP, Cell::DOWN]);
+        }
+        directions
+    }
+
+    /// Returns positions of all the adjacent cells
+    pub fn neighbours(&self, pos: Pos) -> Vec<Pos> {
+        self.directions(pos)
+            .into_iter()
+            .filter_map(|dir| self.get_next_cell_pos(pos, dir).ok())
+            .collect()
+    }
+
+    /// Returns directions of the passages the cell has
+    pub fn carved_directions(&self, pos: Pos) -> Vec<Cell> {
+        self.directions(pos)
+            .into_iter()
+            .filter(|dir| self.is_cell_carved(pos, *dir))
+            .collect()
+    }
+
+    /// Returns positions of the neighbours the cell has passages to
+    pub fn links(&self, pos: Pos) -> Vec<Pos> {
+        self.carved_directions(pos)
+            .into_iter()
+            .filter_map(|dir| self.get_next_cell_pos(pos, dir).ok())
+            .collect()
+    }
+
+    /// Returns the direction leading from the cell to an adjacent one
+    pub fn direction_to(&self, pos: Pos, npos: Pos) -> Option<Cell> {
+        self.directions(pos)
+            .into_iter()
+            .find(|dir| self.get_next_cell_pos(pos, *dir).ok() == Some(npos))
+    }
+
+    /// Carves a passage between two adjacent cells
+    pub fn link(&mut self, pos: Pos, npos: Pos) -> TransitResult<()> {
+        match self.direction_to(pos, npos) {
+            Some(direction) => self.carve_passage(pos, direction).map(|_| ()),
+            None => Err(TransitError {
+                pos: npos,
+                reason: String::from("Cells are not adjacent"),
+            }),
+        }
+    }
+
     pub fn carve_passage(&mut self, pos: Pos, direction: Cell) -> TransitResult<Pos> {
         let npos = self.get_next_cell_pos(pos, direction)?;
+        let back = self.direction_to(npos, pos).unwrap_or_else(|| direction.opposite());
 
-        match direction {
-            Cell::NORTH => {
-                *self.get_cell_mut(pos) |= Cell::NORTH;
-                *self.get_cell_mut(npos) |= Cell::SOUTH;
-            }
-            Cell::SOUTH => {
-                *self.get_cell_mut(pos) |= Cell::SOUTH;
-                *self.get_cell_mut(npos) |= Cell::NORTH;
-            }
-            Cell::EAST => {
-                *self.get_cell_mut(pos) |= Cell::EAST;
-                *self.get_cell_mut(npos) |= Cell::WEST;
-            }
-            Cell::WEST => {
-                *self.get_cell_mut(pos) |= Cell::WEST;
-                *self.get_cell_mut(npos) |= Cell::EAST;
-            }
-            _ => (),
-        }
+        *self.get_cell_mut(pos) |= direction;
+        *self.get_cell_mut(npos) |= back;
 
         self.visit_cell(pos);
         self.visit_cell(npos);
@@ -98,18 +330,57 @@ impl Grid {
         Ok(npos)
     }
 
-    pub fn get_next_cell_pos(&mut self, pos: Pos, direction: Cell) -> TransitResult<Pos> {
-        self.validate_transit(pos, direction)?;
+    pub fn get_next_cell_pos(&self, pos: Pos, direction: Cell) -> TransitResult<Pos> {
+        self.validate_transit(pos, direction)
+    }
 
-        let (x, y) = pos;
-        let (nx, ny) = match direction {
-            Cell::NORTH => (x, y - 1),
-            Cell::SOUTH => (x, y + 1),
-            Cell::WEST => (x - 1, y),
-            Cell::EAST => (x + 1, y),
-            _ => (x, y),
+    /// Checks if the passage in the direction runs under the cell, across the passage carved over it
+    pub fn tunnels_under(&self, pos: Pos, direction: Cell) -> bool {
+        self.is_cell_carved(pos, Cell::UNDER)
+            && self.topology.directions().contains(&direction)
+            && !self.is_cell_carved(pos, direction)
+            && !self.is_cell_carved(pos, direction.opposite())
+    }
+
+    /// Weaves a crossing: carves a passage over the cell from the neighbour in the direction to the opposite one, and
+    /// a tunnel under it between the other two. The cells on both ends of the tunnel become adjacent
+    pub fn add_crossing(&mut self, pos: Pos, over: Cell) -> TransitResult<()> {
+        if self.topology != Topology::Rectangular {
+            return Err(TransitError {
+                pos,
+                reason: format!("Passages cannot cross in a {} grid", self.topology),
+            });
+        }
+
+        let under = if over == Cell::NORTH || over == Cell::SOUTH {
+            Cell::WEST
+        } else {
+            Cell::NORTH
         };
-        Ok((nx, ny))
+        for direction in [over, over.opposite(), under, under.opposite()] {
+            self.get_next_cell_pos(pos, direction)?;
+        }
+
+        self.carve_passage(pos, over)?;
+        self.carve_passage(pos, over.opposite())?;
+        *self.get_cell_mut(pos) |= Cell::UNDER;
+
+        let entrance = self.get_next_cell_pos(pos, under)?;
+        self.carve_passage(entrance, under.opposite())?;
+        Ok(())
+    }
+
+    /// Checks if the neighbour in the direction lies across a wrapped edge of the grid
+    pub fn wraps_around(&self, pos: Pos, direction: Cell) -> bool {
+        if self.wrap == Wrap::None || self.get_next_cell_pos(pos, direction).is_err() {
+            return false;
+        }
+
+        let (x, y) = pos;
+        match self.topology.offset((x, y % self.level_height), direction) {
+            Some((nx, ny)) => nx < 0 || ny < 0 || nx as usize >= self.width || ny as usize >= self.level_height,
+            None => false,
+        }
     }
 
     fn get_cell_status(&self, pos: Pos) -> &CellStatus {
@@ -132,24 +403,88 @@ impl Grid {
         &mut self.cells[y * self.width + x]
     }
 
-    fn validate_transit(&self, pos: Pos, direction: Cell) -> TransitResult<()> {
+    /// How many cells of the ring lie outwards from one cell of the previous ring
+    fn ring_ratio(&self, ring: usize) -> usize {
+        match ring {
+            0 => 1,
+            _ if ring >= self.height => 1,
+            _ => self.row_width(ring) / self.row_width(ring - 1),
+        }
+    }
+
+    /// Returns the position of the neighbour in the polar grid. Rings wrap around, so only moving inwards from the
+    /// first ring or outwards from the last one leads out of the grid
+    fn polar_offset(&self, (x, y): Pos, direction: Cell) -> Option<(isize, isize)> {
+        let ring_width = self.row_width(y);
+        let (nx, ny) = match direction {
+            Cell::NORTH if y == 0 => return Some((x as isize, -1)),
+            Cell::NORTH => (x / self.ring_ratio(y), y - 1),
+            Cell::SOUTH => (x * self.ring_ratio(y + 1), y + 1),
+            Cell::SOUTH_EAST if self.ring_ratio(y + 1) == 2 => (x * 2 + 1, y + 1),
+            Cell::WEST => ((x + ring_width - 1) % ring_width, y),
+            Cell::EAST => ((x + 1) % ring_width, y),
+            _ => return None,
+        };
+        Some((nx as isize, ny as isize))
+    }
+
+    /// Returns the position of the neighbour in the direction if it's in the grid
+    fn validate_transit(&self, pos: Pos, direction: Cell) -> TransitResult<Pos> {
         let (x, y) = pos;
-        let reason = match direction {
-            Cell::NORTH if y < 1 => Some("First row in the grid cannot go North"),
-            Cell::SOUTH if y + 1 == self.height => Some("Last row in the grid cannot go South"),
-            Cell::WEST if x < 1 => Some("First cell in a row cannot go West"),
-            Cell::EAST if x + 1 == self.width => Some("Last column in the grid cannot go East"),
-            _ => None,
+        // neighbours are looked up within the level, the rows of the other levels lie below it
+        let (level, row) = (y / self.level_height, y % self.level_height);
+        let offset = match (self.topology, direction) {
+            (_, Cell::UP) => Some((x as isize, row as isize, level as isize + 1)),
+            (_, Cell::DOWN) => Some((x as isize, row as isize, level as isize - 1)),
+            (Topology::Polar, _) => self.polar_offset(pos, direction).map(|(nx, ny)| (nx, ny, 0)),
+            _ => self
+                .topology
+                .offset((x, row), direction)
+                .map(|(nx, ny)| (nx, ny, level as isize)),
+        };
+        let (mut nx, mut ny, nlevel) = match offset {
+            Some(next) => next,
+            None => {
+                return Err(TransitError {
+                    pos,
+                    reason: format!("Direction {:?} doesn't exist in a {} grid", direction, self.topology),
+                })
+            }
         };
 
-        if reason.is_none() {
-            return Ok(());
+        if self.wrap.horizontal() {
+            nx = nx.rem_euclid(self.width as isize);
+        }
+        if self.wrap.vertical() {
+            ny = ny.rem_euclid(self.level_height as isize);
+        }
+
+        let within_level = nx >= 0 && ny >= 0 && (ny as usize) < self.level_height;
+        if within_level && nlevel >= 0 && (nlevel as usize) < self.levels {
+            let npos = (nx as usize, nlevel as usize * self.level_height + ny as usize);
+            // a passage going across a crossing leads to the cell on the other side of the tunnel
+            if self.contains(npos) && self.tunnels_under(npos, direction) {
+                return self.validate_transit(npos, direction);
+            }
+            if self.contains(npos) {
+                return Ok(npos);
+            }
         }
 
-        return Err(TransitError {
+        let reason = match direction {
+            Cell::NORTH if row < 1 => "First row in the grid cannot go North",
+            Cell::SOUTH if row + 1 == self.level_height => "Last row in the grid cannot go South",
+            Cell::UP if level + 1 == self.levels => "Top level in the grid cannot go Up",
+            Cell::DOWN if level == 0 => "Bottom level in the grid cannot go Down",
+            Cell::WEST if x < 1 => "First cell in a row cannot go West",
+            Cell::EAST if x + 1 == self.row_width(y) => "Last column in the grid cannot go East",
+            _ => "Neighbour lies outside of the grid",
+        };
+
+        Err(TransitError {
             pos,
-            reason: reason.unwrap().to_string(),
-        });
+            reason: reason.to_string(),
+        })
     }
 }
 
@@ -158,8 +493,57 @@ impl Clone for Grid {
         Grid {
             width: self.width,
             height: self.height,
-            cells: self.cells.to_vec(),
-            cell_statuses: self.cell_statuses.to_vec(),
+            levels: self.levels,
+            level_height: self.level_height,
+            topology: self.topology,
+            wrap: self.wrap,
+            row_widths: Arc::clone(&self.row_widths),
+            active: Arc::clone(&self.active),
+            weights: Arc::clone(&self.weights),
+            cells: self.cells.clone(),
+            cell_statuses: self.cell_statuses.clone(),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stacked_levels_are_linked_up_and_down() {
+        let grid = Grid::with_topology(4, 2, Topology::Triangular).stacked(3);
+
+        assert_eq!((grid.height(), grid.levels(), grid.cells_count()), (6, 3, 24));
+        assert_eq!(grid.get_next_cell_pos((1, 1), Cell::UP).ok(), Some((1, 3)));
+        assert_eq!(grid.get_next_cell_pos((1, 3), Cell::DOWN).ok(), Some((1, 1)));
+        assert!(grid.get_next_cell_pos((1, 5), Cell::UP).is_err());
+        // rows don't lead to other levels, and triangles keep pointing the same way on every level
+        assert!(grid.get_next_cell_pos((0, 1), Cell::SOUTH).is_err());
+        assert_eq!(grid.directions((0, 2)), grid.directions((0, 0)));
+    }
+
+    #[test]
+    fn level_is_cut_out_of_stacked_grid() {
+        let mut grid = Grid::new(2, 2).stacked(2);
+        grid.carve_passage((0, 0), Cell::UP).unwrap();
+        grid.carve_passage((0, 2), Cell::EAST).unwrap();
+
+        let level = grid.level(1);
+        assert_eq!((level.height(), level.levels()), (2, 1));
+        assert!(level.is_cell_carved((0, 0), Cell::DOWN) && level.is_cell_carved((0, 0), Cell::EAST));
+        assert_eq!(level.directions((0, 0)), Topology::Rectangular.directions());
+    }
+
+    #[test]
+    fn every_level_has_the_same_weights() {
+        let weights = Weights::from_ascii("19\n.5").unwrap();
+        let grid = Grid::new(3, 2).stacked(2).weighted(&weights);
+
+        assert_eq!(Grid::new(3, 2).weight((1, 0)), 1);
+        assert_eq!(grid.weight((1, 0)), 9);
+        assert_eq!(grid.weight((1, 3)), 5);
+        assert_eq!(grid.weight((2, 2)), 1);
+        assert_eq!(grid.level(1).weight((1, 0)), 9);
+    }
+}
@@ -0,0 +1,178 @@
+use std::{f64::consts::PI, fmt, str::FromStr};
+
+use super::cell::Cell;
+use crate::app::utils::types::Pos;
+
+/// The shape of the cells and the way they are laid out in a grid
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Topology {
+    /// Square cells with four neighbours
+    #[default]
+    Rectangular,
+    /// Flat-topped hexagons with six neighbours. Odd columns are shifted half a cell down
+    Hexagonal,
+    /// Triangles with three neighbours, pointing up and down in turns. The top-left one points up
+    Triangular,
+    /// Concentric rings around a hole, rows are rings from the inside out and columns go clockwise. Outer rings
+    /// split cells in two to keep them about as wide as they are high
+    Polar,
+}
+
+const RECTANGULAR_DIRECTIONS: [Cell; 4] = [Cell::NORTH, Cell::SOUTH, Cell::WEST, Cell::EAST];
+const HEXAGONAL_DIRECTIONS: [Cell; 6] = [
+    Cell::NORTH,
+    Cell::SOUTH,
+    Cell::NORTH_WEST,
+    Cell::NORTH_EAST,
+    Cell::SOUTH_WEST,
+    Cell::SOUTH_EAST,
+];
+/// `NORTH` leads inwards, `SOUTH` and `SOUTH_EAST` to the one or two cells outwards, `EAST` clockwise
+const POLAR_DIRECTIONS: [Cell; 5] = [Cell::NORTH, Cell::SOUTH, Cell::SOUTH_EAST, Cell::WEST, Cell::EAST];
+
+impl Topology {
+    /// All the topologies in the order they are switched in the UI
+    pub const ALL: [Topology; 4] = [
+        Topology::Rectangular,
+        Topology::Hexagonal,
+        Topology::Triangular,
+        Topology::Polar,
+    ];
+
+    /// Directions cells can have passages in, whether there is a neighbour there or not. A triangle only has one of
+    /// `NORTH` and `SOUTH`, see `offset`
+    pub fn directions(&self) -> &'static [Cell] {
+        match self {
+            Topology::Rectangular | Topology::Triangular => &RECTANGULAR_DIRECTIONS,
+            Topology::Hexagonal => &HEXAGONAL_DIRECTIONS,
+            Topology::Polar => &POLAR_DIRECTIONS,
+        }
+    }
+
+    /// The number of cells in every row. Polar grids have as many rings as the grid height, the width is ignored
+    pub fn row_widths(&self, width: usize, height: usize) -> Vec<usize> {
+        match self {
+            Topology::Rectangular | Topology::Hexagonal => vec![width; height],
+            // a single column of triangles falls apart into pairs that have no neighbours below or above
+            Topology::Triangular if height > 1 => vec![width.max(2); height],
+            Topology::Triangular => vec![width; height],
+            Topology::Polar => {
+                let mut rings: Vec<usize> = vec![];
+                for ring in 0..height {
+                    // the hole in the middle is one ring wide, so the inner circle of a ring has the radius ring + 1
+                    let circumference = 2.0 * PI * (ring + 1) as f64;
+                    let count = match rings.last() {
+                        Some(&prev) => prev * (circumference / prev as f64).round().clamp(1.0, 2.0) as usize,
+                        None => circumference.round() as usize,
+                    };
+                    rings.push(count);
+                }
+                rings
+            }
+        }
+    }
+
+    /// Returns the position of the neighbour in the direction, which can lie outside of the grid. Polar neighbours
+    /// depend on the ring sizes and are found by the grid
+    pub fn offset(&self, (x, y): Pos, direction: Cell) -> Option<(isize, isize)> {
+        let (x, y) = (x as isize, y as isize);
+        let (dx, dy) = match (self, direction) {
+            (Topology::Polar, _) => return None,
+            // a triangle pointing up has a neighbour below its base, one pointing down above its base
+            (Topology::Triangular, Cell::NORTH) if Topology::is_pointing_up((x as usize, y as usize)) => return None,
+            (Topology::Triangular, Cell::SOUTH) if !Topology::is_pointing_up((x as usize, y as usize)) => return None,
+            (_, Cell::NORTH) => (0, -1),
+            (_, Cell::SOUTH) => (0, 1),
+            (Topology::Rectangular | Topology::Triangular, Cell::WEST) => (-1, 0),
+            (Topology::Rectangular | Topology::Triangular, Cell::EAST) => (1, 0),
+            // diagonal neighbours of an odd column are half a cell lower than those of an even one
+            (Topology::Hexagonal, Cell::NORTH_WEST) => (-1, (x % 2) - 1),
+            (Topology::Hexagonal, Cell::NORTH_EAST) => (1, (x % 2) - 1),
+            (Topology::Hexagonal, Cell::SOUTH_WEST) => (-1, x % 2),
+            (Topology::Hexagonal, Cell::SOUTH_EAST) => (1, x % 2),
+            _ => return None,
+        };
+        Some((x + dx, y + dy))
+    }
+
+    /// Triangles in a triangular grid point up and down in turns, starting from the top-left one pointing up
+    pub fn is_pointing_up((x, y): Pos) -> bool {
+        (x + y) % 2 == 0
+    }
+
+    pub fn next(&self) -> Topology {
+        let idx = Topology::ALL.iter().position(|t| t == self).unwrap_or(0);
+        Topology::ALL[(idx + 1) % Topology::ALL.len()]
+    }
+}
+
+impl FromStr for Topology {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rect" | "rectangular" => Ok(Topology::Rectangular),
+            "hex" | "hexagonal" => Ok(Topology::Hexagonal),
+            "tri" | "triangular" => Ok(Topology::Triangular),
+            "polar" => Ok(Topology::Polar),
+            _ => Err(format!("Unknown topology '{}'. Expected rect, hex, tri or polar", s)),
+        }
+    }
+}
+
+impl fmt::Display for Topology {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let str = match self {
+            Topology::Rectangular => "rectangular",
+            Topology::Hexagonal => "hexagonal",
+            Topology::Triangular => "triangular",
+            Topology::Polar => "polar",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hexagonal_offsets_depend_on_column() {
+        let hex = Topology::Hexagonal;
+
+        assert_eq!(hex.offset((2, 2), Cell::NORTH_EAST), Some((3, 1)));
+        assert_eq!(hex.offset((2, 2), Cell::SOUTH_WEST), Some((1, 2)));
+        assert_eq!(hex.offset((3, 2), Cell::NORTH_EAST), Some((4, 2)));
+        assert_eq!(hex.offset((3, 2), Cell::SOUTH_WEST), Some((2, 3)));
+        assert_eq!(hex.offset((3, 2), Cell::EAST), None);
+    }
+
+    #[test]
+    fn triangles_have_neighbours_across_their_bases() {
+        let tri = Topology::Triangular;
+
+        assert_eq!(tri.offset((0, 0), Cell::SOUTH), Some((0, 1)));
+        assert_eq!(tri.offset((0, 0), Cell::NORTH), None);
+        assert_eq!(tri.offset((1, 0), Cell::NORTH), Some((1, -1)));
+        assert_eq!(tri.offset((1, 0), Cell::SOUTH), None);
+        assert_eq!(tri.offset((1, 0), Cell::WEST), Some((0, 0)));
+    }
+
+    #[test]
+    fn triangular_grid_is_at_least_two_cells_wide() {
+        assert_eq!(Topology::Triangular.row_widths(1, 3), vec![2, 2, 2]);
+        assert_eq!(Topology::Triangular.row_widths(1, 1), vec![1]);
+    }
+
+    #[test]
+    fn polar_rings_split_cells_in_two() {
+        assert_eq!(Topology::Polar.row_widths(100, 7), vec![6, 12, 24, 24, 24, 48, 48]);
+    }
+
+    #[test]
+    fn parse_topology() {
+        assert_eq!("hex".parse::<Topology>(), Ok(Topology::Hexagonal));
+        assert_eq!("rect".parse::<Topology>(), Ok(Topology::Rectangular));
+        assert!("round".parse::<Topology>().is_err());
+    }
+}
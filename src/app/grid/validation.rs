@@ -0,0 +1,166 @@
+use std::fmt;
+
+use super::{cell::Cell, Grid};
+use crate::app::utils::types::Pos;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    /// A cell has a passage that its neighbour doesn't have back, or the passage leads out of the grid
+    AsymmetricWall { pos: Pos, direction: Cell },
+    /// A passage closes a loop
+    Cycle { pos: Pos },
+    /// A cell cannot be reached from the first cell
+    Unreachable { pos: Pos },
+    /// A perfect maze has exactly one passage less than cells
+    PassagesCount { expected: usize, actual: usize },
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::AsymmetricWall { pos: (x, y), direction } => {
+                write!(f, "Passage {:?} has no way back. Pos: x = {}, y = {}", direction, x, y)
+            }
+            ValidationError::Cycle { pos: (x, y) } => write!(f, "Passages form a loop. Pos: x = {}, y = {}", x, y),
+            ValidationError::Unreachable { pos: (x, y) } => {
+                write!(f, "Cell is unreachable. Pos: x = {}, y = {}", x, y)
+            }
+            ValidationError::PassagesCount { expected, actual } => {
+                write!(f, "Expected {} passages, found {}", expected, actual)
+            }
+        }
+    }
+}
+
+type ValidationResult = Result<(), ValidationError>;
+
+/// Checks that the grid is a perfect maze: every wall is shared by both of its cells and there is exactly one path
+/// between any two cells
+pub fn validate_perfect_maze(grid: &Grid) -> ValidationResult {
+    validate_walls(grid)?;
+    validate_tree(grid)?;
+    validate_passages_count(grid)
+}
+
+fn validate_walls(grid: &Grid) -> ValidationResult {
+    for pos in grid.positions() {
+        for direction in grid.carved_directions(pos) {
+            let symmetric = match grid.get_next_cell_pos(pos, direction) {
+                Ok(next) => grid
+                    .direction_to(next, pos)
+                    .is_some_and(|back| grid.is_cell_carved(next, back)),
+                Err(_) => false,
+            };
+
+            if !symmetric {
+                return Err(ValidationError::AsymmetricWall { pos, direction });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Walks the passages from the first cell remembering where every cell has been entered from. Meeting a visited
+/// cell anywhere but the one we came from means a loop
+fn validate_tree(grid: &Grid) -> ValidationResult {
+    let mut visited = vec![false; grid.width() * grid.height()];
+    let start = match grid.positions().next() {
+        Some(start) => start,
+        None => return Ok(()),
+    };
+    let mut stack: Vec<(Pos, Option<Pos>)> = vec![(start, None)];
+    visited[start.1 * grid.width() + start.0] = true;
+
+    while let Some((pos, parent)) = stack.pop() {
+        for next in grid.links(pos) {
+            if Some(next) == parent {
+                continue;
+            }
+
+            let idx = next.1 * grid.width() + next.0;
+            if visited[idx] {
+                return Err(ValidationError::Cycle { pos: next });
+            }
+
+            visited[idx] = true;
+            stack.push((next, Some(pos)));
+        }
+    }
+
+    match grid.positions().find(|(x, y)| !visited[y * grid.width() + x]) {
+        Some(pos) => Err(ValidationError::Unreachable { pos }),
+        None => Ok(()),
+    }
+}
+
+fn validate_passages_count(grid: &Grid) -> ValidationResult {
+    // every passage is counted from both of its cells
+    let actual = grid.positions().map(|pos| grid.links(pos).len()).sum::<usize>() / 2;
+    let expected = grid.cells_count().saturating_sub(1);
+
+    if actual != expected {
+        return Err(ValidationError::PassagesCount { expected, actual });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_maze() {
+        let mut grid = Grid::new(2, 2);
+        grid.carve_passage((0, 0), Cell::EAST).unwrap();
+        grid.carve_passage((1, 0), Cell::SOUTH).unwrap();
+        grid.carve_passage((1, 1), Cell::WEST).unwrap();
+
+        assert_eq!(validate_perfect_maze(&grid), Ok(()));
+    }
+
+    #[test]
+    fn single_cell() {
+        assert_eq!(validate_perfect_maze(&Grid::new(1, 1)), Ok(()));
+    }
+
+    #[test]
+    fn asymmetric_wall() {
+        let mut grid = Grid::new(2, 1);
+        *grid.get_cell_mut((0, 0)) |= Cell::EAST;
+
+        assert_eq!(
+            validate_perfect_maze(&grid),
+            Err(ValidationError::AsymmetricWall {
+                pos: (0, 0),
+                direction: Cell::EAST
+            })
+        );
+    }
+
+    #[test]
+    fn maze_with_loop() {
+        let mut grid = Grid::new(2, 2);
+        grid.carve_passage((0, 0), Cell::EAST).unwrap();
+        grid.carve_passage((1, 0), Cell::SOUTH).unwrap();
+        grid.carve_passage((1, 1), Cell::WEST).unwrap();
+        grid.carve_passage((0, 1), Cell::NORTH).unwrap();
+
+        assert!(matches!(
+            validate_perfect_maze(&grid),
+            Err(ValidationError::Cycle { .. })
+        ));
+    }
+
+    #[test]
+    fn unreachable_cell() {
+        let mut grid = Grid::new(2, 2);
+        grid.carve_passage((0, 0), Cell::EAST).unwrap();
+        grid.carve_passage((1, 0), Cell::SOUTH).unwrap();
+
+        assert_eq!(
+            validate_perfect_maze(&grid),
+            Err(ValidationError::Unreachable { pos: (0, 1) })
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::{fmt, fs, path::Path};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::app::utils::types::Pos;
+
+/// The cost of the cheapest cell to walk through
+pub const MIN_WEIGHT: usize = 1;
+/// The cost of the most expensive cell to walk through
+pub const MAX_WEIGHT: usize = 9;
+
+/// Terrain costs of walking through the cells of a grid. Cells the weights don't cover cost the least
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Weights {
+    width: usize,
+    height: usize,
+    costs: Vec<usize>,
+}
+
+/// Where the weights of the cells come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Terrain {
+    /// Hills of costly cells scattered with the seed of the maze
+    Random,
+    /// The same weights for every maze
+    Map(Weights),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WeightsError {
+    /// The weights file cannot be read
+    Io(String),
+    /// A character is neither a cost nor a cheapest cell
+    InvalidCost { pos: Pos, found: char },
+    /// There are no cells
+    Empty,
+}
+
+impl fmt::Display for WeightsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WeightsError::Io(reason) => write!(f, "Cannot read the weights. Reason: {}", reason),
+            WeightsError::InvalidCost { pos: (x, y), found } => write!(
+                f,
+                "Expected a cost from {} to {}, found '{}'. Pos: x = {}, y = {}",
+                MIN_WEIGHT, MAX_WEIGHT, found, x, y
+            ),
+            WeightsError::Empty => write!(f, "The weights have no cells"),
+        }
+    }
+}
+
+type WeightsResult = Result<Weights, WeightsError>;
+
+impl Weights {
+    pub fn load(path: &Path) -> WeightsResult {
+        let text = fs::read_to_string(path).map_err(|err| WeightsError::Io(err.to_string()))?;
+        Weights::from_ascii(&text)
+    }
+
+    /// Reads a text template where every character is a cell costing from 1 to 9. Dots and spaces cost the least.
+    /// Shorter lines are padded with the cheapest cells
+    pub fn from_ascii(text: &str) -> WeightsResult {
+        let lines = text
+            .lines()
+            .map(|line| line.trim_end())
+            .skip_while(|line| line.is_empty())
+            .collect::<Vec<_>>();
+        let height = lines.iter().rposition(|line| !line.is_empty()).map_or(0, |idx| idx + 1);
+        let width = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+        if width == 0 {
+            return Err(WeightsError::Empty);
+        }
+
+        let mut costs = vec![MIN_WEIGHT; width * height];
+        for (y, line) in lines.iter().take(height).enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                costs[y * width + x] = match c {
+                    '.' | ' ' => MIN_WEIGHT,
+                    _ => match c.to_digit(10).map(|cost| cost as usize) {
+                        Some(cost) if (MIN_WEIGHT..=MAX_WEIGHT).contains(&cost) => cost,
+                        _ => return Err(WeightsError::InvalidCost { pos: (x, y), found: c }),
+                    },
+                };
+            }
+        }
+
+        Ok(Weights { width, height, costs })
+    }
+
+    /// Raises hills of costly cells over flat land. The same seed always raises the same hills
+    pub fn random(width: usize, height: usize, seed: u64) -> Weights {
+        let mut rng = ChaCha8Rng::seed_from_u64(seed);
+        let mut costs = vec![MIN_WEIGHT; width * height];
+
+        // a hill for every 12 cells keeps enough flat land to go around them
+        for _ in 0..(width * height / 12).max(1) {
+            let (hx, hy) = (rng.gen_range(0..width.max(1)), rng.gen_range(0..height.max(1)));
+            let radius = rng.gen_range(1..=3usize);
+
+            for y in hy.saturating_sub(radius)..(hy + radius + 1).min(height) {
+                for x in hx.saturating_sub(radius)..(hx + radius + 1).min(width) {
+                    let distance = x.abs_diff(hx) + y.abs_diff(hy);
+                    if distance <= radius {
+                        let cost = &mut costs[y * width + x];
+                        *cost = (*cost + (radius - distance + 1) * 2).min(MAX_WEIGHT);
+                    }
+                }
+            }
+        }
+
+        Weights { width, height, costs }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn cost(&self, (x, y): Pos) -> usize {
+        match x < self.width && y < self.height {
+            true => self.costs[y * self.width + x],
+            false => MIN_WEIGHT,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_template() {
+        let weights = Weights::from_ascii("19.\n 5\n\n").unwrap();
+
+        assert_eq!((weights.width(), weights.height()), (3, 2));
+        assert_eq!(weights.cost((1, 0)), 9);
+        assert_eq!(weights.cost((1, 1)), 5);
+        assert_eq!(weights.cost((2, 0)), 1);
+        assert_eq!(weights.cost((2, 1)), 1);
+        assert_eq!(weights.cost((7, 7)), 1);
+    }
+
+    #[test]
+    fn blank_lines_around_the_template_are_skipped() {
+        let weights = Weights::from_ascii("\n  \n19.\n 5\n\n").unwrap();
+
+        assert_eq!(weights, Weights::from_ascii("19.\n 5").unwrap());
+        assert_eq!(
+            Weights::from_ascii("\n12\n30"),
+            Err(WeightsError::InvalidCost {
+                pos: (1, 1),
+                found: '0'
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_weights() {
+        assert_eq!(
+            Weights::from_ascii("12\n30"),
+            Err(WeightsError::InvalidCost {
+                pos: (1, 1),
+                found: '0'
+            })
+        );
+        assert_eq!(Weights::from_ascii("\n\n"), Err(WeightsError::Empty));
+    }
+
+    #[test]
+    fn random_weights_are_seeded() {
+        let weights = Weights::random(12, 8, 5);
+
+        assert_eq!(weights, Weights::random(12, 8, 5));
+        assert_ne!(weights, Weights::random(12, 8, 6));
+        assert!(weights
+            .costs
+            .iter()
+            .all(|cost| (MIN_WEIGHT..=MAX_WEIGHT).contains(cost)));
+        assert!(weights.costs.contains(&MIN_WEIGHT) && weights.costs.iter().any(|cost| *cost > MIN_WEIGHT));
+    }
+}
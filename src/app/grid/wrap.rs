@@ -0,0 +1,115 @@
+use std::{fmt, str::FromStr};
+
+/// Edges of the grid that lead to the opposite edge, turning it into a cylinder or a torus
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Wrap {
+    #[default]
+    None,
+    /// The first and the last column are adjacent, like on a cylinder standing upright
+    Horizontal,
+    /// The first and the last row are adjacent, like on a cylinder lying down
+    Vertical,
+    /// Both columns and rows wrap around, like on a torus
+    Both,
+}
+
+impl Wrap {
+    pub fn new(horizontal: bool, vertical: bool) -> Wrap {
+        match (horizontal, vertical) {
+            (false, false) => Wrap::None,
+            (true, false) => Wrap::Horizontal,
+            (false, true) => Wrap::Vertical,
+            (true, true) => Wrap::Both,
+        }
+    }
+
+    pub fn horizontal(&self) -> bool {
+        matches!(self, Wrap::Horizontal | Wrap::Both)
+    }
+
+    pub fn vertical(&self) -> bool {
+        matches!(self, Wrap::Vertical | Wrap::Both)
+    }
+}
+
+impl FromStr for Wrap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Wrap::None),
+            "h" | "horizontal" | "cylinder" => Ok(Wrap::Horizontal),
+            "v" | "vertical" => Ok(Wrap::Vertical),
+            "both" | "torus" => Ok(Wrap::Both),
+            _ => Err(format!(
+                "Unknown wrap '{}'. Expected none, horizontal, vertical or both",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Wrap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let str = match self {
+            Wrap::None => "none",
+            Wrap::Horizontal => "horizontal",
+            Wrap::Vertical => "vertical",
+            Wrap::Both => "both",
+        };
+        write!(f, "{}", str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::grid::{cell::Cell, topology::Topology, Grid};
+
+    #[test]
+    fn torus_corners_are_adjacent() {
+        let grid = Grid::new(4, 3).wrapped(Wrap::Both);
+
+        assert_eq!(grid.get_next_cell_pos((0, 0), Cell::WEST).ok(), Some((3, 0)));
+        assert_eq!(grid.get_next_cell_pos((0, 0), Cell::NORTH).ok(), Some((0, 2)));
+        assert_eq!(grid.get_next_cell_pos((3, 2), Cell::SOUTH).ok(), Some((3, 0)));
+        assert!(grid.wraps_around((3, 2), Cell::EAST) && !grid.wraps_around((2, 2), Cell::EAST));
+    }
+
+    #[test]
+    fn cylinder_keeps_top_and_bottom_edges() {
+        let grid = Grid::new(4, 3).wrapped(Wrap::Horizontal);
+
+        assert_eq!(grid.get_next_cell_pos((3, 1), Cell::EAST).ok(), Some((0, 1)));
+        assert!(grid.get_next_cell_pos((0, 0), Cell::NORTH).is_err());
+    }
+
+    #[test]
+    fn odd_sized_grids_only_wrap_where_cells_line_up() {
+        // hexagons and triangles alternate between columns, so the edges only meet if there is an even number
+        assert_eq!(
+            Grid::with_topology(5, 4, Topology::Hexagonal)
+                .wrapped(Wrap::Both)
+                .wrap(),
+            Wrap::Vertical
+        );
+        assert_eq!(
+            Grid::with_topology(6, 5, Topology::Triangular)
+                .wrapped(Wrap::Both)
+                .wrap(),
+            Wrap::Horizontal
+        );
+        assert_eq!(Grid::new(2, 3).wrapped(Wrap::Both).wrap(), Wrap::Vertical);
+        assert_eq!(
+            Grid::with_topology(1, 5, Topology::Polar).wrapped(Wrap::Both).wrap(),
+            Wrap::None
+        );
+    }
+
+    #[test]
+    fn parse_wrap() {
+        assert_eq!("torus".parse::<Wrap>(), Ok(Wrap::Both));
+        assert_eq!("cylinder".parse::<Wrap>(), Ok(Wrap::Horizontal));
+        assert!("sphere".parse::<Wrap>().is_err());
+    }
+}
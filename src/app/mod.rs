@@ -3,17 +3,26 @@ use log::{debug, warn};
 use crate::event::Key;
 
 use self::{
-    actions::{Action, Actions},
+    actions::{Action, Actions, Controls},
+    algos::Descriptor,
+    game::Fog,
+    grid::{mask::Mask, topology::Topology, weights::Terrain, wrap::Wrap},
+    solvers::{solve, ISolver},
     state::AppState,
 };
 
 mod actions;
-mod algos;
-mod grid;
-mod state;
+pub mod algos;
+pub mod game;
+pub mod grid;
+mod svg;
 mod utils;
 mod widgets;
 
+pub mod scores;
+pub mod solvers;
+pub mod state;
+pub mod stats;
 pub mod ui;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -31,29 +40,110 @@ pub struct App<'a> {
 }
 
 impl<'a> App<'a> {
-    pub fn new(title: &'a str, width: usize, height: usize) -> Self {
-        let actions = vec![
-            Action::Quit,
-            Action::SelectNextAlgo,
-            Action::SelectPrevAlgo,
-            Action::RunMazeGeneration,
-        ]
-        .into();
-        let state = AppState::new(width, height);
+    pub fn new(
+        title: &'a str,
+        width: usize,
+        height: usize,
+        topology: Topology,
+        mask: Option<Mask>,
+        wrap: Wrap,
+        levels: usize,
+    ) -> Self {
+        let actions = App::contextual_actions(0);
+        let state = AppState::new(width, height, topology, mask, wrap, levels);
         Self { title, actions, state }
     }
 
+    /// Braids every generated maze, removing the share of its dead ends
+    pub fn with_braid(mut self, ratio: f64) -> Self {
+        self.state.braid = ratio;
+        self
+    }
+
+    /// Generates every maze with the seed instead of a random one
+    pub fn with_seed(mut self, seed: Option<u64>) -> Self {
+        self.state.seed = seed;
+        self
+    }
+
+    /// Gives the cells of every generated maze costs of walking through them
+    pub fn with_terrain(mut self, terrain: Option<Terrain>) -> Self {
+        self.state.terrain = terrain;
+        self
+    }
+
+    /// Adds a solver of your own to the solver menu, after the built-in ones
+    pub fn with_solver<S: ISolver>(mut self, title: &'a str) -> Self {
+        self.state.solvers.items.push((title, solve::<S>));
+        self
+    }
+
+    /// Adds a generator of your own to the algorithm menu, after the built-in ones
+    pub fn with_generator(mut self, descriptor: Descriptor) -> Self {
+        self.state.algorithms.items.push(descriptor);
+        self
+    }
+
+    /// Selects the generator with the id in the algorithm menu instead of the first one
+    pub fn with_algo(mut self, id: Option<&str>) -> Self {
+        if let Some(id) = id {
+            match self
+                .state
+                .algorithms
+                .items
+                .iter()
+                .position(|descriptor| descriptor.id == id)
+            {
+                Some(idx) => self.state.algorithms.state.select(Some(idx)),
+                None => warn!("There's no algorithm with the id {}", id),
+            }
+        }
+        self
+    }
+
+    /// Tunes the generators taking the parameters with the names
+    pub fn with_params(mut self, params: Vec<(String, f64)>) -> Self {
+        self.state.params = params;
+        self
+    }
+
+    /// Sets how far the player sees when playing in the fog
+    pub fn with_fog(mut self, fog: Fog) -> Self {
+        self.state.fog = fog;
+        self
+    }
+
     pub fn do_action(&mut self, key: Key) -> AppReturn {
         if let Some(action) = self.actions.find(key) {
             debug!("Run action [{:?}]", action);
+            let players = self.players();
 
             match action {
                 Action::SelectNextAlgo => self.state.select_next_algo(),
                 Action::SelectPrevAlgo => self.state.select_prev_algo(),
                 Action::RunMazeGeneration => self.state.start_maze_generation(),
+                Action::ToggleSetColors => self.state.toggle_set_colors(),
+                Action::ToggleComparedAlgo => self.state.toggle_compared_algo(),
+                Action::SwitchTopology => self.state.switch_topology(),
+                Action::ExportSvg => self.state.export_svg(),
+                Action::LevelUp => self.state.level_up(),
+                Action::LevelDown => self.state.level_down(),
+                Action::Play => self.state.start_game(1, false),
+                Action::PlayInFog => self.state.start_game(1, true),
+                Action::Race => self.state.start_game(2, false),
+                Action::StopPlaying => self.state.stop_game(),
+                Action::Move(controls, direction) => self.state.move_player(controls.player(), *direction),
+                Action::ToggleHighScores => self.state.toggle_high_scores(),
+                Action::Solve => self.state.solve_mazes(),
+                Action::SelectNextSolver => self.state.select_next_solver(),
                 Action::Quit => return AppReturn::Exit,
             };
 
+            // the arrow keys move the players in the game instead of going through the algorithms
+            if self.players() != players {
+                self.actions = App::contextual_actions(self.players());
+            }
+
             AppReturn::Continue
         } else {
             warn!("No action accociated to {}", key);
@@ -61,6 +151,50 @@ impl<'a> App<'a> {
         }
     }
 
+    /// The number of players in the game, zero if nobody plays
+    fn players(&self) -> usize {
+        self.state.game.as_ref().map_or(0, |game| game.players())
+    }
+
+    fn contextual_actions(players: usize) -> Actions {
+        // racing players split the keyboard, a single player can use either half
+        let controls = match players {
+            0 => vec![],
+            1 => vec![Controls::Any],
+            _ => vec![Controls::Wasd, Controls::Arrows],
+        };
+        let moves = controls
+            .into_iter()
+            .flat_map(|controls| Controls::DIRECTIONS.map(|direction| Action::Move(controls, direction)));
+
+        let actions = if players > 0 {
+            let mut actions = vec![Action::Quit];
+            actions.extend(moves);
+            actions.extend([Action::ToggleHighScores, Action::StopPlaying]);
+            actions
+        } else {
+            vec![
+                Action::Quit,
+                Action::SelectNextAlgo,
+                Action::SelectPrevAlgo,
+                Action::RunMazeGeneration,
+                Action::ToggleSetColors,
+                Action::ToggleComparedAlgo,
+                Action::SwitchTopology,
+                Action::ExportSvg,
+                Action::LevelUp,
+                Action::LevelDown,
+                Action::Play,
+                Action::PlayInFog,
+                Action::Race,
+                Action::ToggleHighScores,
+                Action::Solve,
+                Action::SelectNextSolver,
+            ]
+        };
+        actions.into()
+    }
+
     pub fn update_on_tick(&mut self) -> AppReturn {
         self.state.on_tick();
         AppReturn::Continue
@@ -0,0 +1,451 @@
+use std::{
+    env,
+    ffi::OsString,
+    fmt, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use log::warn;
+
+use super::{
+    game::Fog,
+    grid::{topology::Topology, wrap::Wrap},
+};
+
+/// The number of scores shown for a maze
+pub const TOP_SCORES: usize = 10;
+
+/// A maze generated by the algorithm on a grid of the shape with the seed. The same seed gives everyone the same maze
+/// as long as everything else matches, so every field tells the leaderboards apart
+#[derive(Debug, Clone, PartialEq)]
+pub struct Maze {
+    /// The id of the generator, so the scores outlive a renamed title
+    pub algorithm: String,
+    pub topology: Topology,
+    /// The size of a level of the grid
+    pub width: usize,
+    pub height: usize,
+    pub levels: usize,
+    pub wrap: Wrap,
+    /// A fingerprint of the mask shaping the grid, `none` without one
+    pub mask: String,
+    /// The share of the dead ends removed from the maze
+    pub braid: f64,
+    /// `random`, a fingerprint of the map of the cell costs, or `none` if the cells cost the same
+    pub terrain: String,
+    /// How far the player saw, if the maze was covered in fog
+    pub fog: Option<Fog>,
+    pub seed: u64,
+}
+
+/// The grid and the seed. The algorithm is left to the caller, which knows its title
+impl fmt::Display for Maze {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}x{}", self.topology, self.width, self.height)?;
+        if self.levels > 1 {
+            write!(f, "x{}", self.levels)?;
+        }
+        if let Some(fog) = self.fog {
+            write!(f, " · fog {}", fog)?;
+        }
+        write!(f, " · seed {}", self.seed)
+    }
+}
+
+/// Tells files apart by their contents, the same on every platform and version
+pub fn fingerprint(bytes: impl IntoIterator<Item = u8>) -> String {
+    // 64-bit FNV-1a
+    let hash = bytes.into_iter().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// A game won in the maze
+#[derive(Debug, Clone, PartialEq)]
+pub struct Score {
+    pub maze: Maze,
+    pub player: String,
+    pub time: Duration,
+    pub moves: usize,
+}
+
+impl Score {
+    /// Writes the score as a line of tab separated values. Tabs in the names are replaced with spaces
+    fn to_line(&self) -> String {
+        let maze = &self.maze;
+        let fog = match maze.fog {
+            Some(Fog::LineOfSight) => String::from("sight"),
+            Some(Fog::Steps(steps)) => steps.to_string(),
+            None => String::from("none"),
+        };
+        [
+            maze.algorithm.replace('\t', " "),
+            maze.topology.to_string(),
+            maze.width.to_string(),
+            maze.height.to_string(),
+            maze.levels.to_string(),
+            maze.wrap.to_string(),
+            maze.mask.clone(),
+            maze.braid.to_string(),
+            maze.terrain.clone(),
+            fog,
+            maze.seed.to_string(),
+            self.player.replace('\t', " "),
+            self.time.as_millis().to_string(),
+            self.moves.to_string(),
+        ]
+        .join("\t")
+    }
+
+    fn from_line(line: &str) -> Result<Score, String> {
+        let fields = line.split('\t').collect::<Vec<_>>();
+        let [algorithm, topology, width, height, levels, wrap, mask, braid, terrain, fog, seed, player, time, moves] =
+            fields[..]
+        else {
+            return Err(format!("Expected 14 fields, found {}", fields.len()));
+        };
+        let number = |field: &str| {
+            field
+                .parse::<u64>()
+                .map_err(|_| format!("Expected a number, found '{}'", field))
+        };
+
+        Ok(Score {
+            maze: Maze {
+                algorithm: algorithm.to_string(),
+                topology: topology.parse()?,
+                width: number(width)? as usize,
+                height: number(height)? as usize,
+                levels: number(levels)? as usize,
+                wrap: wrap.parse()?,
+                mask: mask.to_string(),
+                braid: braid
+                    .parse()
+                    .map_err(|_| format!("Expected a braid ratio, found '{}'", braid))?,
+                terrain: terrain.to_string(),
+                fog: match fog {
+                    "none" => None,
+                    fog => Some(fog.parse()?),
+                },
+                seed: number(seed)?,
+            },
+            player: player.to_string(),
+            time: Duration::from_millis(number(time)?),
+            moves: number(moves)? as usize,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScoreError {
+    /// The file cannot be read or written
+    Io(String),
+    /// A line of the file is not a score
+    InvalidLine { line: usize, reason: String },
+}
+
+impl fmt::Display for ScoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ScoreError::Io(reason) => write!(f, "Cannot access the high scores. Reason: {}", reason),
+            ScoreError::InvalidLine { line, reason } => {
+                write!(f, "Invalid high score on line {}. Reason: {}", line, reason)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for ScoreError {
+    fn from(err: io::Error) -> Self {
+        ScoreError::Io(err.to_string())
+    }
+}
+
+/// Scores of the won games kept in a file with a score per line, so players sharing it can compete on the same maze
+pub struct HighScores {
+    /// `None` if there's nowhere to save the scores, they're only kept until the app exits
+    path: Option<PathBuf>,
+    scores: Vec<Score>,
+    loaded: bool,
+}
+
+impl HighScores {
+    /// Points to the file without reading it. A missing file has no scores
+    pub fn new(path: PathBuf) -> Self {
+        HighScores {
+            path: Some(path),
+            scores: vec![],
+            loaded: false,
+        }
+    }
+
+    /// Keeps the scores without a file, for a user without a data directory
+    pub fn in_memory() -> Self {
+        HighScores {
+            path: None,
+            scores: vec![],
+            loaded: false,
+        }
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Reads the scores once, the later calls keep the scores read and recorded since. Lines that aren't scores are
+    /// skipped with a warning, so a broken line doesn't cost the rest of the scores
+    pub fn load(&mut self) -> Result<(), ScoreError> {
+        if self.loaded {
+            return Ok(());
+        }
+        let Some(path) = &self.path else {
+            warn!("No data directory to save the scores in, they're kept until the app exits");
+            self.loaded = true;
+            return Ok(());
+        };
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(err) => return Err(err.into()),
+        };
+        self.scores = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(idx, line)| match Score::from_line(line) {
+                Ok(score) => Some(score),
+                Err(reason) => {
+                    warn!("{}", ScoreError::InvalidLine { line: idx + 1, reason });
+                    None
+                }
+            })
+            .collect();
+        self.loaded = true;
+        Ok(())
+    }
+
+    /// Appends the score to the file, creating it if needed. Returns the rank of the score in its maze
+    pub fn record(&mut self, score: Score) -> Result<usize, ScoreError> {
+        self.load()?;
+
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{}", score.to_line())?;
+        }
+
+        self.scores.push(score.clone());
+        let rank = self.top(&score.maze).iter().position(|top| **top == score).unwrap_or(0);
+        Ok(rank + 1)
+    }
+
+    /// Scores set in the maze, the fastest first. Ties go to fewer moves
+    pub fn top(&self, maze: &Maze) -> Vec<&Score> {
+        let mut top = self
+            .scores
+            .iter()
+            .filter(|score| score.maze == *maze)
+            .collect::<Vec<_>>();
+        top.sort_by_key(|score| (score.time, score.moves));
+        top
+    }
+}
+
+/// `scores.tsv` in the `daedalus` folder of the user's data directory: `$XDG_DATA_HOME` or `~/.local/share`, and
+/// `%LOCALAPPDATA%` or `%APPDATA%` on Windows. `None` if none of them is set
+pub fn default_path() -> Option<PathBuf> {
+    data_dir(cfg!(windows), env::var_os).map(|dir| dir.join("daedalus").join("scores.tsv"))
+}
+
+fn data_dir(windows: bool, var: impl Fn(&'static str) -> Option<OsString>) -> Option<PathBuf> {
+    let var = |name| var(name).filter(|value| !value.is_empty()).map(PathBuf::from);
+    match windows {
+        true => var("LOCALAPPDATA").or_else(|| var("APPDATA")),
+        false => var("XDG_DATA_HOME").or_else(|| var("HOME").map(|home| home.join(".local").join("share"))),
+    }
+}
+
+/// The name of the user running the app
+pub fn player_name() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or_else(|_| String::from("anonymous"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(seed: u64) -> Maze {
+        Maze {
+            algorithm: String::from("kruskal"),
+            topology: Topology::Rectangular,
+            width: 10,
+            height: 8,
+            levels: 1,
+            wrap: Wrap::None,
+            mask: String::from("none"),
+            braid: 0.0,
+            terrain: String::from("none"),
+            fog: None,
+            seed,
+        }
+    }
+
+    fn score(seed: u64, player: &str, time: u64, moves: usize) -> Score {
+        Score {
+            maze: maze(seed),
+            player: player.to_string(),
+            time: Duration::from_millis(time),
+            moves,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("daedalus-test-{}-{}", name, std::process::id()))
+            .join("scores.tsv")
+    }
+
+    #[test]
+    fn scores_are_ranked_by_time_and_moves() {
+        let path = temp_path("rank");
+        let _ = fs::remove_file(&path);
+        let mut scores = HighScores::new(path.clone());
+
+        assert_eq!(scores.record(score(1, "ann", 5000, 40)), Ok(1));
+        assert_eq!(scores.record(score(1, "bob", 3000, 50)), Ok(1));
+        assert_eq!(scores.record(score(1, "cid", 3000, 45)), Ok(1));
+        assert_eq!(scores.record(score(2, "dan", 9000, 99)), Ok(1));
+
+        // the scores survive a restart
+        let mut reloaded = HighScores::new(path.clone());
+        reloaded.load().unwrap();
+        let players = reloaded
+            .top(&maze(1))
+            .iter()
+            .map(|score| score.player.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(players, ["cid", "bob", "ann"]);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn every_setting_has_its_own_leaderboard() {
+        let path = temp_path("settings");
+        let _ = fs::remove_file(&path);
+        let mut scores = HighScores::new(path.clone());
+
+        let mazes = [
+            Maze {
+                topology: Topology::Triangular,
+                ..maze(1)
+            },
+            Maze { levels: 2, ..maze(1) },
+            Maze {
+                wrap: Wrap::Both,
+                ..maze(1)
+            },
+            Maze {
+                mask: fingerprint(*b"##.#"),
+                ..maze(1)
+            },
+            Maze { braid: 0.5, ..maze(1) },
+            Maze {
+                terrain: String::from("random"),
+                ..maze(1)
+            },
+            Maze {
+                fog: Some(Fog::Steps(3)),
+                ..maze(1)
+            },
+            maze(1),
+        ];
+        for maze in mazes.iter() {
+            let score = Score {
+                maze: maze.clone(),
+                ..score(1, "ann", 1000, 10)
+            };
+            assert_eq!(scores.record(score), Ok(1), "{}", maze);
+        }
+
+        let mut reloaded = HighScores::new(path.clone());
+        reloaded.load().unwrap();
+        for maze in mazes.iter() {
+            assert_eq!(reloaded.top(maze).len(), 1, "{}", maze);
+        }
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn invalid_lines_are_skipped() {
+        let path = temp_path("invalid");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let line = score(1, "ann", 1, 1).to_line();
+        fs::write(
+            &path,
+            format!(
+                "{}\n\n{}\nkruskal\t10\t8\t1\tann\t1\t1\n",
+                line,
+                line.replace("rectangular", "square")
+            ),
+        )
+        .unwrap();
+
+        let mut scores = HighScores::new(path.clone());
+        assert_eq!(scores.load(), Ok(()));
+        assert_eq!(scores.top(&maze(1)), [&score(1, "ann", 1, 1)]);
+        assert!(HighScores::new(temp_path("missing")).load().is_ok());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn data_dir_follows_xdg() {
+        let dir = |windows: bool, vars: &[(&str, &str)]| {
+            data_dir(windows, |name| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| OsString::from(value))
+            })
+        };
+
+        assert_eq!(
+            dir(false, &[("XDG_DATA_HOME", "/data"), ("HOME", "/home/ann")]),
+            Some(PathBuf::from("/data"))
+        );
+        assert_eq!(
+            dir(false, &[("XDG_DATA_HOME", ""), ("HOME", "/home/ann")]),
+            Some(PathBuf::from("/home/ann/.local/share"))
+        );
+        assert_eq!(
+            dir(true, &[("LOCALAPPDATA", "C:\\Local"), ("APPDATA", "C:\\Roaming")]),
+            Some(PathBuf::from("C:\\Local"))
+        );
+        assert_eq!(
+            dir(true, &[("APPDATA", "C:\\Roaming"), ("HOME", "/home/ann")]),
+            Some(PathBuf::from("C:\\Roaming"))
+        );
+        // the scores are never written to the working directory
+        assert_eq!(dir(false, &[("APPDATA", "C:\\Roaming")]), None);
+        assert_eq!(dir(true, &[("HOME", "/home/ann")]), None);
+    }
+
+    #[test]
+    fn scores_without_a_data_directory_are_kept_in_memory() {
+        let mut scores = HighScores::in_memory();
+
+        assert_eq!(scores.record(score(1, "ann", 5000, 40)), Ok(1));
+        assert_eq!(scores.record(score(1, "bob", 3000, 50)), Ok(1));
+        assert_eq!(scores.top(&maze(1)).len(), 2);
+        assert_eq!(scores.path(), None);
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::VecDeque;
+
+use crate::app::{
+    grid::Grid,
+    state::{MazeSnapshot, SharedVec},
+    utils::types::Pos,
+};
+
+use super::{path_through, ISolver, Search};
+
+/// Fills the dead ends one cell at a time until every passage left leads somewhere. Only the solution remains of a
+/// perfect maze, while the loops of a braid maze never fill up and stay around the solution
+pub struct DeadEndFilling {
+    grid: Grid,
+    start: Pos,
+    goal: Pos,
+    snapshots: Vec<MazeSnapshot>,
+}
+
+impl ISolver for DeadEndFilling {
+    fn init(grid: Grid, start: Pos, goal: Pos) -> Self {
+        Self {
+            grid,
+            start,
+            goal,
+            snapshots: vec![],
+        }
+    }
+
+    /// Fills a cell at every step, highlighting it. A filled dead end can turn the cell it led to into a new one,
+    /// which is filled next. The last snapshot shows the path through the cells left
+    fn run(&mut self) -> Vec<MazeSnapshot> {
+        let width = self.grid.width();
+        let idx = |(x, y): Pos| y * width + x;
+        let mut filled = SharedVec::from_elem(false, width * self.grid.height());
+
+        let mut dead_ends = self
+            .grid
+            .positions()
+            .filter(|pos| self.is_dead_end(*pos, &filled))
+            .collect::<VecDeque<_>>();
+
+        while let Some(pos) = dead_ends.pop_front() {
+            if filled[idx(pos)] || !self.is_dead_end(pos, &filled) {
+                continue;
+            }
+            filled[idx(pos)] = true;
+
+            dead_ends.extend(
+                self.grid
+                    .links(pos)
+                    .into_iter()
+                    .filter(|npos| self.is_dead_end(*npos, &filled)),
+            );
+
+            let search = Search {
+                explored: filled.clone(),
+                ..Default::default()
+            };
+            self.snapshots
+                .push(MazeSnapshot::new(self.grid.clone(), vec![pos].into(), SharedVec::default()).with_search(search));
+        }
+
+        let search = Search {
+            path: path_through(&self.grid, self.start, self.goal, |_, npos| !filled[idx(npos)]).into(),
+            explored: filled,
+            shortest: vec![],
+        };
+        self.snapshots
+            .push(MazeSnapshot::new(self.grid.clone(), SharedVec::default(), SharedVec::default()).with_search(search));
+
+        self.snapshots.clone()
+    }
+}
+
+impl DeadEndFilling {
+    /// Checks if at most one passage of the cell leads to a cell that isn't filled. The start and the goal are never
+    /// filled
+    fn is_dead_end(&self, pos: Pos, filled: &SharedVec<bool>) -> bool {
+        let width = self.grid.width();
+        let open = self
+            .grid
+            .links(pos)
+            .into_iter()
+            .filter(|(x, y)| !filled[y * width + x])
+            .count();
+
+        pos != self.start && pos != self.goal && !filled[pos.1 * width + pos.0] && open <= 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        algos::{Braid, IGenerator, RecursiveBacktracking},
+    };
+
+    #[test]
+    fn only_the_solution_is_left_of_perfect_maze() {
+        let maze = RecursiveBacktracking::init(Grid::new(8, 6), 4).run();
+        let grid = maze.last().unwrap().get_grid().clone();
+
+        let snapshots = DeadEndFilling::init(grid.clone(), (0, 0), (7, 5)).run();
+        let search = snapshots.last().unwrap().get_search().unwrap();
+        let filled = search.explored.iter().filter(|filled| **filled).count();
+
+        // a cell is filled at every step
+        assert_eq!(snapshots.len(), filled + 1);
+        assert_eq!(filled + search.path.len(), grid.cells_count());
+    }
+
+    #[test]
+    fn loops_of_braid_maze_are_not_filled() {
+        let maze = RecursiveBacktracking::init(Grid::new(8, 6), 4).run();
+        let braid = Braid::new(maze.last().unwrap().get_grid().clone(), 4, 1.0).run();
+        let grid = braid.last().unwrap().get_grid().clone();
+
+        let snapshots = DeadEndFilling::init(grid, (0, 0), (7, 5)).run();
+
+        assert_eq!(snapshots.len(), 1);
+        assert!(!snapshots[0].get_search().unwrap().path.is_empty());
+    }
+}
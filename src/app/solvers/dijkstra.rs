@@ -0,0 +1,257 @@
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+use crate::app::{
+    grid::{cell::Cell, topology::Topology, Grid},
+    state::{MazeSnapshot, SharedVec},
+    utils::types::Pos,
+};
+
+use super::{shortest_path, ISolver, Search};
+
+/// Finds the cheapest path through weighted cells by settling them in the order of their cost from the start. With
+/// the heuristic it becomes A*, which settles the cells that look closer to the goal first
+pub struct Dijkstra {
+    grid: Grid,
+    start: Pos,
+    goal: Pos,
+    heuristic: Option<Heuristic>,
+    snapshots: Vec<MazeSnapshot>,
+}
+
+impl Dijkstra {
+    /// Guides the search towards the goal, turning it into A*
+    pub fn with_heuristic(mut self) -> Self {
+        self.heuristic = Some(Heuristic {
+            min_weight: self
+                .grid
+                .positions()
+                .map(|pos| self.grid.weight(pos))
+                .min()
+                .unwrap_or(0),
+            crossings: self
+                .grid
+                .positions()
+                .any(|pos| self.grid.is_cell_carved(pos, Cell::UNDER)),
+        });
+        self
+    }
+
+    /// The least the rest of the way to the goal can cost: the fewest passages to it through the cheapest cells.
+    /// It never overestimates, so A* still finds the cheapest path. Dijkstra's algorithm doesn't estimate
+    fn estimate(&self, pos: Pos) -> usize {
+        match &self.heuristic {
+            Some(heuristic) => min_passages(&self.grid, pos, self.goal, heuristic.crossings) * heuristic.min_weight,
+            None => 0,
+        }
+    }
+}
+
+impl ISolver for Dijkstra {
+    fn init(grid: Grid, start: Pos, goal: Pos) -> Self {
+        Self {
+            grid,
+            start,
+            goal,
+            heuristic: None,
+            snapshots: vec![],
+        }
+    }
+
+    /// Settles a cell from the priority queue at every step. A snapshot highlights the cells waiting in the queue and
+    /// shows the path to the cell just settled. The last one compares the cheapest path with the shortest one
+    fn run(&mut self) -> Vec<MazeSnapshot> {
+        let width = self.grid.width();
+        let idx = |(x, y): Pos| y * width + x;
+
+        let mut costs = vec![None; width * self.grid.height()];
+        let mut parents = vec![None; width * self.grid.height()];
+        // the path to every settled cell extends the one to its parent, so they share the cells in common
+        let mut paths = vec![SharedVec::default(); width * self.grid.height()];
+        let mut explored = SharedVec::from_elem(false, width * self.grid.height());
+        let mut queue = BinaryHeap::from([Reverse((self.estimate(self.start), 0, self.start))]);
+        costs[idx(self.start)] = Some(0);
+
+        while let Some(Reverse((_, cost, pos))) = queue.pop() {
+            // a cell is queued again whenever a cheaper way to it is found, the stale entries are skipped
+            if explored[idx(pos)] {
+                continue;
+            }
+            explored[idx(pos)] = true;
+            let mut path = match parents[idx(pos)] {
+                Some(parent) => paths[idx(parent)].clone(),
+                None => SharedVec::default(),
+            };
+            path.push(pos);
+            paths[idx(pos)] = path;
+
+            for npos in self.grid.links(pos) {
+                let ncost = cost + self.grid.weight(npos);
+                if explored[idx(npos)] || costs[idx(npos)].is_some_and(|known| known <= ncost) {
+                    continue;
+                }
+                costs[idx(npos)] = Some(ncost);
+                parents[idx(npos)] = Some(pos);
+                queue.push(Reverse((ncost + self.estimate(npos), ncost, npos)));
+            }
+
+            let mut frontier = queue
+                .iter()
+                .map(|Reverse((_, _, pos))| *pos)
+                .filter(|pos| !explored[idx(*pos)])
+                .collect::<Vec<_>>();
+            frontier.sort();
+            frontier.dedup();
+            let search = Search {
+                explored: explored.clone(),
+                path: paths[idx(pos)].clone(),
+                shortest: vec![],
+            };
+            self.snapshots
+                .push(MazeSnapshot::new(self.grid.clone(), frontier.into(), SharedVec::default()).with_search(search));
+
+            if pos == self.goal {
+                break;
+            }
+        }
+
+        let search = Search {
+            explored: SharedVec::default(),
+            path: paths[idx(self.goal)].clone(),
+            shortest: shortest_path(&self.grid, self.start, self.goal),
+        };
+        self.snapshots
+            .push(MazeSnapshot::new(self.grid.clone(), SharedVec::default(), SharedVec::default()).with_search(search));
+
+        self.snapshots.clone()
+    }
+}
+
+/// Dijkstra's algorithm guided towards the goal
+pub struct AStar(Dijkstra);
+
+impl ISolver for AStar {
+    fn init(grid: Grid, start: Pos, goal: Pos) -> Self {
+        AStar(Dijkstra::init(grid, start, goal).with_heuristic())
+    }
+
+    fn run(&mut self) -> Vec<MazeSnapshot> {
+        self.0.run()
+    }
+}
+
+/// What A* knows about the whole grid to estimate the cost of the rest of the way
+struct Heuristic {
+    min_weight: usize,
+    /// Tunnels under crossings lead two cells away
+    crossings: bool,
+}
+
+/// The fewest passages between the cells if there were no walls. A passage changes a coordinate by one, apart from
+/// hexagons moving along both axes at once and tunnels under crossings skipping a cell
+fn min_passages(grid: &Grid, (x, y): Pos, (gx, gy): Pos, crossings: bool) -> usize {
+    let height = grid.level_height();
+    let (row, grow) = (y % height, gy % height);
+    let levels = (y / height).abs_diff(gy / height);
+
+    let mut dx = x.abs_diff(gx);
+    let mut dy = row.abs_diff(grow);
+    if grid.wrap().horizontal() {
+        dx = dx.min(grid.width() - dx);
+    }
+    if grid.wrap().vertical() {
+        dy = dy.min(height - dy);
+    }
+
+    let passages = match grid.topology() {
+        Topology::Rectangular | Topology::Triangular => dx + dy,
+        Topology::Hexagonal => dx.max(dy),
+        // cells of the rings line up differently, only the rings to cross are known
+        Topology::Polar => dy,
+    };
+    match crossings {
+        true => passages.div_ceil(2) + levels,
+        false => passages + levels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        algos::{Braid, IGenerator, Kruskal},
+        grid::weights::Weights,
+        solvers::{path_cost, solve},
+    };
+
+    fn last_search(snapshots: &[MazeSnapshot]) -> &Search {
+        snapshots.last().unwrap().get_search().unwrap()
+    }
+
+    /// Carves every passage of a 3x2 grid and makes the middle of the first row costly:
+    ///  _____
+    /// |1 9 1|
+    /// |1_1_1|
+    fn open_grid() -> Grid {
+        let mut grid = Grid::new(3, 2).weighted(&Weights::from_ascii("191\n111").unwrap());
+        for pos in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+            grid.carve_passage(pos, Cell::EAST).unwrap();
+        }
+        for x in 0..3 {
+            grid.carve_passage((x, 0), Cell::SOUTH).unwrap();
+        }
+        grid
+    }
+
+    #[test]
+    fn cheapest_path_goes_around_costly_cells() {
+        let grid = open_grid();
+
+        for solver in [solve::<Dijkstra>, solve::<AStar>] {
+            let snapshots = solver(grid.clone(), (0, 0), (2, 0));
+            let search = last_search(&snapshots);
+
+            assert_eq!(search.path.to_vec(), [(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]);
+            assert_eq!(search.shortest, [(0, 0), (1, 0), (2, 0)]);
+            assert_eq!(path_cost(&grid, &search.path.to_vec()), 4);
+            assert_eq!(path_cost(&grid, &search.shortest), 10);
+        }
+    }
+
+    #[test]
+    fn every_step_settles_a_cell() {
+        let grid = open_grid();
+        let snapshots = Dijkstra::init(grid, (0, 0), (2, 0)).run();
+
+        // every cell but the costly one is settled before the goal, then the solution is shown
+        assert_eq!(snapshots.len(), 6);
+        let first = snapshots[0].get_search().unwrap();
+        assert_eq!(first.path.to_vec(), [(0, 0)]);
+        assert_eq!(snapshots[0].get_highlights(), &SharedVec::from(vec![(0, 1), (1, 0)]));
+        let explored = &snapshots[4].get_search().unwrap().explored;
+        assert_eq!(explored.iter().filter(|explored| **explored).count(), 5);
+    }
+
+    #[test]
+    fn a_star_finds_as_cheap_a_path_exploring_less() {
+        for seed in 0..10 {
+            let maze = Kruskal::init(Grid::new(12, 10), seed).run();
+            let maze = maze.last().unwrap().get_grid().clone();
+            let braid = Braid::new(maze, seed, 1.0).run();
+            let grid = braid
+                .last()
+                .unwrap()
+                .get_grid()
+                .clone()
+                .weighted(&Weights::random(12, 10, seed));
+            let (start, goal) = ((0, 0), (11, 9));
+
+            let dijkstra = Dijkstra::init(grid.clone(), start, goal).run();
+            let a_star = AStar::init(grid.clone(), start, goal).run();
+            let cheapest = path_cost(&grid, &last_search(&dijkstra).path.to_vec());
+
+            assert_eq!(path_cost(&grid, &last_search(&a_star).path.to_vec()), cheapest);
+            assert!(cheapest <= path_cost(&grid, &last_search(&dijkstra).shortest));
+            assert!(a_star.len() <= dijkstra.len(), "seed {}", seed);
+        }
+    }
+}
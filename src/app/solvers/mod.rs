@@ -0,0 +1,211 @@
+use std::{collections::VecDeque, ops::Range};
+
+use super::{
+    grid::Grid,
+    state::{MazeSnapshot, SharedVec},
+    utils::types::Pos,
+};
+
+pub mod dead_end_filling;
+pub mod dijkstra;
+pub mod tremaux;
+
+pub use dead_end_filling::DeadEndFilling;
+pub use dijkstra::{AStar, Dijkstra};
+pub use tremaux::Tremaux;
+
+/// What a solver knows about the maze at a step of the search
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Search {
+    /// Cells the solver has settled, indexed as `y * width + x`. Clones share it until it's written
+    pub explored: SharedVec<bool>,
+    /// The best path found so far from the start, the cheapest one to the goal once the search is over. Clones share
+    /// the cells they have in common
+    pub path: SharedVec<Pos>,
+    /// The path with the fewest passages from the start to the goal, shown next to the cheapest one
+    pub shortest: Vec<Pos>,
+}
+
+impl Search {
+    /// Keeps the cells of the rows, moved up to the first one
+    pub fn rows(&self, rows: Range<usize>, width: usize) -> Search {
+        let cut = |path: &mut dyn Iterator<Item = &Pos>| -> Vec<Pos> {
+            path.filter(|(_, y)| rows.contains(y))
+                .map(|(x, y)| (*x, y - rows.start))
+                .collect()
+        };
+
+        Search {
+            explored: self
+                .explored
+                .slice(rows.start * width..rows.end * width)
+                .unwrap_or_default(),
+            path: cut(&mut self.path.iter()).into(),
+            shortest: cut(&mut self.shortest.iter()),
+        }
+    }
+}
+
+/// A solver searching a maze for a path between two cells. Implement it to add a solver of your own to the menu with
+/// `App::with_solver`
+pub trait ISolver {
+    /// Prepares a solver for the finished maze. The search goes from the start to the goal
+    fn init(grid: Grid, start: Pos, goal: Pos) -> Self;
+    /// Searches the maze and returns the snapshots of its every step. The last one shows the path found, an empty
+    /// path if there's no way to the goal
+    fn run(&mut self) -> Vec<MazeSnapshot>;
+}
+
+/// Searches the maze from the start to the goal and returns the snapshots of every step
+pub type Solver = fn(Grid, Pos, Pos) -> Vec<MazeSnapshot>;
+
+/// Runs the solver over the maze to the end. Solvers are added to the menu as this function
+pub fn solve<S: ISolver>(grid: Grid, start: Pos, goal: Pos) -> Vec<MazeSnapshot> {
+    S::init(grid, start, goal).run()
+}
+
+/// All the built-in solvers with their titles, in the order of the menu. Dijkstra's algorithm and A* find the cheapest
+/// path, the others walk the maze the way people do and ignore the weights
+pub fn solvers<'a>() -> Vec<(&'a str, Solver)> {
+    vec![
+        ("Dijkstra's", solve::<Dijkstra>),
+        ("A*", solve::<AStar>),
+        ("Dead-end filling", solve::<DeadEndFilling>),
+        ("Trémaux's", solve::<Tremaux>),
+    ]
+}
+
+/// The cost of walking the path: the weights of the cells it enters
+pub fn path_cost(grid: &Grid, path: &[Pos]) -> usize {
+    path.iter().skip(1).map(|pos| grid.weight(*pos)).sum()
+}
+
+/// Finds a path with the fewest passages between the cells regardless of their weights. Empty if there's none
+pub fn shortest_path(grid: &Grid, start: Pos, goal: Pos) -> Vec<Pos> {
+    path_through(grid, start, goal, |_, _| true)
+}
+
+/// Finds a path with the fewest passages the solver lets through. Empty if there's none
+fn path_through(grid: &Grid, start: Pos, goal: Pos, can_pass: impl Fn(Pos, Pos) -> bool) -> Vec<Pos> {
+    let width = grid.width();
+    let mut parents = vec![None; width * grid.height()];
+    let mut seen = vec![false; width * grid.height()];
+    let mut queue = VecDeque::from([start]);
+    seen[start.1 * width + start.0] = true;
+
+    while let Some(pos) = queue.pop_front() {
+        if pos == goal {
+            return trace(&parents, goal, width);
+        }
+
+        for (nx, ny) in grid.links(pos) {
+            if !seen[ny * width + nx] && can_pass(pos, (nx, ny)) {
+                seen[ny * width + nx] = true;
+                parents[ny * width + nx] = Some(pos);
+                queue.push_back((nx, ny));
+            }
+        }
+    }
+    vec![]
+}
+
+/// Walks the parents back from the cell to the start
+fn trace(parents: &[Option<Pos>], mut pos: Pos, width: usize) -> Vec<Pos> {
+    let mut path = vec![pos];
+    while let Some(parent) = parents[pos.1 * width + pos.0] {
+        path.push(parent);
+        pos = parent;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        algos::{find, Braid},
+        grid::{topology::Topology, weights::Weights},
+    };
+
+    const SEEDS_COUNT: u64 = 10;
+
+    fn mazes(seed: u64) -> Vec<(&'static str, Grid)> {
+        let maze = |id: &str, grid: Grid| {
+            find(id)
+                .unwrap()
+                .generate(grid, seed)
+                .last()
+                .unwrap()
+                .get_grid()
+                .clone()
+        };
+        let perfect = maze("prim", Grid::new(9, 7));
+
+        vec![
+            (
+                "braid",
+                Braid::new(perfect.clone(), seed, 0.7)
+                    .run()
+                    .last()
+                    .unwrap()
+                    .get_grid()
+                    .clone(),
+            ),
+            ("perfect", perfect),
+            (
+                "hexagonal",
+                maze("kruskal", Grid::with_topology(6, 5, Topology::Hexagonal)),
+            ),
+            (
+                "triangular",
+                maze("kruskal", Grid::with_topology(7, 4, Topology::Triangular)),
+            ),
+            ("stacked", maze("hunt-and-kill", Grid::new(4, 3).stacked(3))),
+            ("weave", maze("kruskal-weave", Grid::new(8, 8))),
+            (
+                "weighted",
+                maze("aldous-broder", Grid::new(8, 6)).weighted(&Weights::random(8, 6, seed)),
+            ),
+        ]
+    }
+
+    #[test]
+    fn every_solver_finds_a_way_through() {
+        for seed in 0..SEEDS_COUNT {
+            for (name, grid) in mazes(seed) {
+                let (start, goal) = (grid.positions().next().unwrap(), grid.positions().last().unwrap());
+                let shortest = shortest_path(&grid, start, goal);
+
+                for (title, solver) in solvers() {
+                    let snapshots = solver(grid.clone(), start, goal);
+                    let path = &snapshots.last().unwrap().get_search().unwrap().path.to_vec();
+
+                    assert_eq!(
+                        (path.first(), path.last()),
+                        (Some(&start), Some(&goal)),
+                        "{} found no way through the {} maze with seed {}",
+                        title,
+                        name,
+                        seed
+                    );
+                    assert!(
+                        path.windows(2).all(|step| grid.links(step[0]).contains(&step[1])),
+                        "{} walked through walls of the {} maze with seed {}",
+                        title,
+                        name,
+                        seed
+                    );
+                    // there's only one way through a perfect maze
+                    if name != "braid" {
+                        assert_eq!(
+                            path, &shortest,
+                            "{} strayed in the {} maze with seed {}",
+                            title, name, seed
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
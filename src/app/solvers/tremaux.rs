@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use crate::app::{
+    grid::Grid,
+    state::{MazeSnapshot, SharedVec},
+    utils::types::Pos,
+};
+
+use super::{shortest_path, ISolver, Search};
+
+/// Walks the maze marking every passage it takes. It turns back when it finds itself in a cell it's been in, and
+/// never takes a passage marked twice. The passages marked once lead from the start to where it stands
+pub struct Tremaux {
+    grid: Grid,
+    start: Pos,
+    goal: Pos,
+    /// The number of times every passage has been taken, by its cells in order
+    marks: HashMap<(Pos, Pos), usize>,
+    snapshots: Vec<MazeSnapshot>,
+}
+
+impl ISolver for Tremaux {
+    fn init(grid: Grid, start: Pos, goal: Pos) -> Self {
+        Self {
+            grid,
+            start,
+            goal,
+            marks: HashMap::new(),
+            snapshots: vec![],
+        }
+    }
+
+    /// Takes a passage at every step, highlighting the cell it leads to. The snapshots show the cells walked through
+    /// and the passages marked once. The last one compares them with the shortest path
+    fn run(&mut self) -> Vec<MazeSnapshot> {
+        let width = self.grid.width();
+        let mut visited = SharedVec::from_elem(false, width * self.grid.height());
+        let (mut pos, mut came_from) = (self.start, None);
+        // the passages marked once in the order they were taken, from the start to where it stands
+        let mut path = SharedVec::from(vec![self.start]);
+
+        loop {
+            let revisited = visited[pos.1 * width + pos.0];
+            visited[pos.1 * width + pos.0] = true;
+            // a new passage led to a cell walked through before, so it only closes a loop
+            let closes_loop = revisited && came_from.is_some_and(|prev| self.marks(pos, prev) == 1);
+
+            let search = Search {
+                explored: visited.clone(),
+                // the way to the cell doesn't go around the loop
+                path: match closes_loop {
+                    true => path
+                        .iter()
+                        .position(|cell| *cell == pos)
+                        .and_then(|end| path.slice(0..end + 1))
+                        .unwrap_or_default(),
+                    false => path.clone(),
+                },
+                shortest: vec![],
+            };
+            self.snapshots
+                .push(MazeSnapshot::new(self.grid.clone(), vec![pos].into(), SharedVec::default()).with_search(search));
+
+            if pos == self.goal {
+                break;
+            }
+
+            let next = match closes_loop {
+                true => came_from,
+                false => self.least_marked(pos, came_from),
+            };
+            match next {
+                Some(next) => {
+                    let marks = self.marks.entry(passage(pos, next)).or_default();
+                    *marks += 1;
+                    // a passage marked twice is the last one of the path, walked back
+                    match *marks {
+                        1 => path.push(next),
+                        _ => {
+                            path.pop();
+                        }
+                    }
+                    came_from = Some(pos);
+                    pos = next;
+                }
+                // every passage has been taken twice, the goal cannot be reached
+                None => break,
+            }
+        }
+
+        let search = Search {
+            explored: visited,
+            path: match pos == self.goal {
+                true => path,
+                false => SharedVec::default(),
+            },
+            shortest: shortest_path(&self.grid, self.start, self.goal),
+        };
+        self.snapshots
+            .push(MazeSnapshot::new(self.grid.clone(), SharedVec::default(), SharedVec::default()).with_search(search));
+
+        self.snapshots.clone()
+    }
+}
+
+impl Tremaux {
+    fn marks(&self, pos: Pos, npos: Pos) -> usize {
+        self.marks.get(&passage(pos, npos)).copied().unwrap_or(0)
+    }
+
+    /// Prefers a passage never taken to going back, and never takes a passage the third time
+    fn least_marked(&self, pos: Pos, came_from: Option<Pos>) -> Option<Pos> {
+        let links = self.grid.links(pos);
+        links
+            .iter()
+            .find(|npos| Some(**npos) != came_from && self.marks(pos, **npos) == 0)
+            .or_else(|| links.iter().find(|npos| self.marks(pos, **npos) == 1))
+            .copied()
+    }
+}
+
+/// Names a passage by its cells in order, so it's the same from both ends
+fn passage(pos: Pos, npos: Pos) -> (Pos, Pos) {
+    (pos.min(npos), pos.max(npos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::{
+        algos::{Braid, HuntAndKill, IGenerator, RecursiveBacktracking},
+    };
+
+    #[test]
+    fn no_passage_is_taken_more_than_twice() {
+        for seed in 0..10 {
+            let maze = HuntAndKill::init(Grid::new(9, 7), seed).run();
+            let braid = Braid::new(maze.last().unwrap().get_grid().clone(), seed, 0.5).run();
+            let grid = braid.last().unwrap().get_grid().clone();
+
+            let mut tremaux = Tremaux::init(grid.clone(), (0, 0), (8, 6));
+            let snapshots = tremaux.run();
+            let path = &snapshots.last().unwrap().get_search().unwrap().path.to_vec();
+
+            assert!(tremaux.marks.values().all(|marks| *marks <= 2), "seed {}", seed);
+            assert_eq!((path.first(), path.last()), (Some(&(0, 0)), Some(&(8, 6))));
+            assert!(path.windows(2).all(|step| grid.links(step[0]).contains(&step[1])));
+        }
+    }
+
+    #[test]
+    fn walking_back_marks_dead_ends_twice() {
+        let maze = RecursiveBacktracking::init(Grid::new(8, 6), 2).run();
+        let grid = maze.last().unwrap().get_grid().clone();
+
+        let mut tremaux = Tremaux::init(grid.clone(), (0, 0), (7, 5));
+        let snapshots = tremaux.run();
+        let search = snapshots.last().unwrap().get_search().unwrap();
+
+        // the only way through a perfect maze is marked once, every dead end walked into is marked twice
+        assert_eq!(search.path.to_vec(), search.shortest);
+        assert_eq!(
+            tremaux.marks.values().filter(|marks| **marks == 1).count(),
+            search.path.len() - 1
+        );
+        assert_eq!(snapshots.len(), tremaux.marks.values().sum::<usize>() + 2);
+    }
+}
@@ -1,24 +1,26 @@
-use std::time::Instant;
+use std::{fs, time::Instant};
 
-use log::info;
+use log::{error, info, warn};
 use tui::widgets::ListState;
 
 use crate::app::{
-    algos::{AldousBroder, Eller, HuntAndKill, IGenerator, Kruskal, Prim, RecursiveBacktracking, Sidewinder},
-    grid::Grid,
+    algos::{generators, Braid, Descriptor},
+    game::{Fog, Game},
+    grid::{
+        cell::Cell,
+        mask::{Mask, MaskError},
+        topology::Topology,
+        weights::{Terrain, Weights},
+        wrap::Wrap,
+        Grid,
+    },
+    scores::{self, HighScores, Maze, Score},
+    stats::MazeStats,
+    solvers::{self, path_cost, shortest_path, Search, Solver},
+    svg,
     utils::types::Pos,
 };
 
-pub enum Algorithm {
-    RecursiveBacktracking,
-    Prims,
-    HuntAndKill,
-    Kruskal,
-    AldousBroder,
-    Eller,
-    Sidewinder,
-}
-
 pub struct StatefulList<T> {
     pub state: ListState,
     pub items: Vec<T>,
@@ -61,23 +63,76 @@ impl<T> StatefulList<T> {
     }
 }
 
+pub use crate::app::utils::shared_vec::SharedVec;
+
+/// The set id of the cells that aren't in a set shown yet
+pub const NO_SET: u32 = u32::MAX;
+
 pub struct MazeSnapshot {
     grid: Grid,
-    highlights: Vec<Pos>,
+    highlights: SharedVec<Pos>,
+    /// Set id of every cell, indexed as `y * width + x`, or `NO_SET`. Empty if the algorithm doesn't track sets
+    sets: SharedVec<u32>,
+    /// The progress of a solver searching the maze. Generators don't search
+    search: Option<Search>,
 }
 
 impl MazeSnapshot {
-    pub fn new(grid: Grid, highlights: Vec<Pos>) -> Self {
-        Self { grid, highlights }
+    pub fn new(grid: Grid, highlights: SharedVec<Pos>, sets: SharedVec<u32>) -> Self {
+        Self {
+            grid,
+            highlights,
+            sets,
+            search: None,
+        }
+    }
+
+    pub fn with_search(mut self, search: Search) -> Self {
+        self.search = Some(search);
+        self
     }
 
     pub fn get_grid(&self) -> &Grid {
         &self.grid
     }
 
-    pub fn get_highlights(&self) -> &Vec<Pos> {
+    pub fn get_highlights(&self) -> &SharedVec<Pos> {
         &self.highlights
     }
+
+    pub fn get_sets(&self) -> &SharedVec<u32> {
+        &self.sets
+    }
+
+    pub fn get_search(&self) -> Option<&Search> {
+        self.search.as_ref()
+    }
+
+    /// Cuts a level out of the snapshot of a stacked grid, with the highlights and sets of its cells
+    pub fn level(&self, level: usize) -> MazeSnapshot {
+        let grid = self.grid.level(level);
+        let first_row = level.min(self.grid.levels() - 1) * grid.height();
+        let rows = first_row..first_row + grid.height();
+
+        let highlights = self
+            .highlights
+            .iter()
+            .filter(|(_, y)| rows.contains(y))
+            .map(|(x, y)| (*x, y - first_row))
+            .collect();
+        let sets = self
+            .sets
+            .slice(rows.start * grid.width()..rows.end * grid.width())
+            .unwrap_or_default();
+        let search = self.search.as_ref().map(|search| search.rows(rows, grid.width()));
+
+        MazeSnapshot {
+            grid,
+            highlights,
+            sets,
+            search,
+        }
+    }
 }
 
 impl Clone for MazeSnapshot {
@@ -85,18 +140,120 @@ impl Clone for MazeSnapshot {
         Self {
             grid: self.grid.clone(),
             highlights: self.highlights.clone(),
+            sets: self.sets.clone(),
+            search: self.search.clone(),
+        }
+    }
+}
+
+/// Playback of the snapshots produced by one generator, followed by the ones of the solver searching the maze
+pub struct MazeRun {
+    pub algo_idx: usize,
+    pub seed: u64,
+    pub snapshots: Vec<MazeSnapshot>,
+    pub step: usize,
+    /// The number of snapshots taken by the generator
+    generated: usize,
+    /// The index of the solver whose snapshots follow the generator's ones
+    pub solver_idx: Option<usize>,
+    /// Metrics of the maze in the current snapshot, computed once when the run moves to it
+    stats: MazeStats,
+}
+
+impl MazeRun {
+    pub fn new(algo_idx: usize, seed: u64, snapshots: Vec<MazeSnapshot>) -> Self {
+        let stats = snapshots
+            .first()
+            .map(|snapshot| MazeStats::from_grid(snapshot.get_grid()))
+            .unwrap_or_default();
+        Self {
+            algo_idx,
+            seed,
+            generated: snapshots.len(),
+            snapshots,
+            step: 0,
+            solver_idx: None,
+            stats,
+        }
+    }
+
+    /// Replaces the search of the previous solver, and plays the new one back from the finished maze
+    fn solve(&mut self, solver_idx: usize, search: Vec<MazeSnapshot>) {
+        self.snapshots.truncate(self.generated);
+        self.snapshots.extend(search);
+        self.step = self.generated.saturating_sub(1);
+        self.solver_idx = Some(solver_idx);
+        self.update_stats();
+    }
+
+    pub fn get_curr_snapshot(&self) -> Option<&MazeSnapshot> {
+        self.snapshots.get(self.step)
+    }
+
+    pub fn steps_count(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    pub fn get_stats(&self) -> &MazeStats {
+        &self.stats
+    }
+
+    fn update_stats(&mut self) {
+        if let Some(snapshot) = self.get_curr_snapshot() {
+            self.stats = MazeStats::from_grid(snapshot.get_grid());
+        }
+    }
+
+    /// Moves to the next snapshot. Returns `false` if the run has already reached the last one
+    fn advance(&mut self) -> bool {
+        if self.step + 1 < self.snapshots.len() {
+            self.step += 1;
+            // solvers don't carve, the maze stays the one of the last generator snapshot
+            if self.step < self.generated {
+                self.update_stats();
+            }
+            true
+        } else {
+            false
         }
     }
 }
 
+/// The max number of generators that can be compared side by side
+pub const MAX_COMPARED_ALGOS: usize = 4;
+
 pub struct AppState<'a> {
     pub grid_width: usize,
     pub grid_height: usize,
+    pub topology: Topology,
+    /// Switches off cells of the grid, its size overrides the grid width and height
+    pub mask: Option<Mask>,
+    pub wrap: Wrap,
+    /// The number of levels stacked on top of each other
+    pub levels: usize,
+    /// The level shown on the screen, counting from the bottom one
+    pub level: usize,
+    /// The share of the dead ends removed from every generated maze
+    pub braid: f64,
+    /// Costs of walking through the cells of every generated maze. The cells cost the same if there's none
+    pub terrain: Option<Terrain>,
+    pub solvers: StatefulList<(&'a str, Solver)>,
     pub is_generator_running: bool,
-    pub algorithms: StatefulList<(&'a str, Algorithm)>,
-    pub snapshots: Option<Vec<MazeSnapshot>>,
-    pub curr_algo_idx: usize,
-    pub running_algo_idx: Option<usize>,
+    pub algorithms: StatefulList<Descriptor>,
+    /// Values of the generator parameters by their names, the parameters missing here take their defaults
+    pub params: Vec<(String, f64)>,
+    /// Indexes of the algorithms marked for the side-by-side comparison, in the order of marking
+    pub compared_algos: Vec<usize>,
+    pub runs: Vec<MazeRun>,
+    pub show_sets: bool,
+    /// The game played in the maze of the first pane
+    pub game: Option<Game>,
+    /// How far the player sees when playing in the fog
+    pub fog: Fog,
+    /// Seeds the generators, so everyone gets the same mazes. A random seed is drawn for every maze if there's none
+    pub seed: Option<u64>,
+    pub scores: HighScores,
+    pub show_scores: bool,
 }
 
 impl<'a> Default for AppState<'a> {
@@ -104,28 +261,45 @@ impl<'a> Default for AppState<'a> {
         AppState {
             grid_width: 10,
             grid_height: 10,
+            topology: Topology::default(),
+            mask: None,
+            wrap: Wrap::default(),
+            levels: 1,
+            level: 0,
+            braid: 0.0,
+            terrain: None,
+            solvers: StatefulList::with_items(solvers::solvers()),
             is_generator_running: false,
-            snapshots: None,
-            curr_algo_idx: 0,
-            running_algo_idx: None,
-            algorithms: StatefulList::with_items(vec![
-                ("Recursive Backtracker", Algorithm::RecursiveBacktracking),
-                ("Prim's", Algorithm::Prims),
-                ("Hunt & Kill", Algorithm::HuntAndKill),
-                ("Kruskal's", Algorithm::Kruskal),
-                ("Aldou-Broder's", Algorithm::AldousBroder),
-                ("Eller's", Algorithm::Eller),
-                ("Sidewinder", Algorithm::Sidewinder),
-            ]),
+            compared_algos: vec![],
+            runs: vec![],
+            show_sets: false,
+            game: None,
+            fog: Fog::default(),
+            seed: None,
+            scores: scores::default_path().map_or_else(HighScores::in_memory, HighScores::new),
+            show_scores: false,
+            algorithms: StatefulList::with_items(generators()),
+            params: vec![],
         }
     }
 }
 
 impl<'a> AppState<'a> {
-    pub fn new(grid_width: usize, grid_height: usize) -> AppState<'a> {
+    pub fn new(
+        grid_width: usize,
+        grid_height: usize,
+        topology: Topology,
+        mask: Option<Mask>,
+        wrap: Wrap,
+        levels: usize,
+    ) -> AppState<'a> {
         AppState {
             grid_width,
             grid_height,
+            topology,
+            mask,
+            wrap,
+            levels,
             ..Default::default()
         }
     }
@@ -142,79 +316,420 @@ impl<'a> AppState<'a> {
         }
     }
 
-    pub fn on_tick(&mut self) {
-        if self.algorithms.state.selected().is_none() {
-            self.algorithms.next();
+    pub fn toggle_set_colors(&mut self) {
+        self.show_sets = !self.show_sets;
+        info!("🎨 Set colors {}", if self.show_sets { "on" } else { "off" });
+    }
+
+    /// Shows the level above the current one
+    pub fn level_up(&mut self) {
+        if self.level + 1 < self.levels {
+            self.level += 1;
+            info!("🪜 Level {}/{}", self.level + 1, self.levels);
         }
+    }
 
-        if !self.is_generator_running {
+    /// Shows the level below the current one
+    pub fn level_down(&mut self) {
+        if self.level > 0 {
+            self.level -= 1;
+            info!("🪜 Level {}/{}", self.level + 1, self.levels);
+        }
+    }
+
+    /// Starts a game in the maze of the first pane once it's been generated. Two or more players race each other. In
+    /// the fog the players only see the cells in sight
+    pub fn start_game(&mut self, players: usize, in_fog: bool) {
+        if self.is_generator_running {
+            warn!("Wait for the maze to be generated to play it");
             return;
         }
 
-        if self.get_next_snapshot().is_some() {
-            self.curr_algo_idx += 1;
-        } else {
-            self.is_generator_running = false;
-            info!("🔃 Performed {} iterations to draw a maze", self.curr_algo_idx);
+        let grid = match self.runs.first().and_then(|run| run.get_curr_snapshot()) {
+            Some(snapshot) => snapshot.get_grid().clone(),
+            None => {
+                warn!("Nothing to play, generate a maze first");
+                return;
+            }
+        };
+
+        let topology = grid.topology();
+        let supported = Game::supports(&grid);
+        match Game::new(grid) {
+            Some(game) => {
+                let game = game.with_players(players);
+                self.level = game.player_level(0);
+                self.game = Some(match in_fog {
+                    true => game.with_fog(self.fog),
+                    false => game,
+                });
+                match players {
+                    1 => info!("🎮 Find the way from the top left cell to the bottom right one"),
+                    _ => info!(
+                        "🏁 Race to the bottom right cell! Player 1 moves with WASD, player 2 with the arrow keys"
+                    ),
+                }
+                if in_fog {
+                    info!("🌫️ The maze is covered in fog. Sight: {}", self.fog);
+                }
+            }
+            None if supported => warn!("A maze of a single cell has no way to find"),
+            None => warn!("Mazes on a {} grid cannot be played", topology),
         }
     }
 
-    pub fn start_maze_generation(&mut self) {
+    pub fn select_next_solver(&mut self) {
+        if !self.is_generator_running {
+            self.solvers.next();
+        }
+    }
+
+    /// Searches the mazes on the screen for a path from the top left cell to the bottom right one with the selected
+    /// solver, and plays the search back
+    pub fn solve_mazes(&mut self) {
         if self.is_generator_running {
+            warn!("Wait for the mazes to be generated to solve them");
+            return;
+        }
+        if self.runs.is_empty() {
+            warn!("Nothing to solve, generate a maze first");
             return;
         }
 
-        if let Some(idx) = self.algorithms.state.selected() {
-            if let Some(algo) = self.algorithms.items.get(idx) {
-                let snapshots = match algo.1 {
-                    Algorithm::Prims => self.generate_maze::<Prim>(),
-                    Algorithm::RecursiveBacktracking => self.generate_maze::<RecursiveBacktracking>(),
-                    Algorithm::HuntAndKill => self.generate_maze::<HuntAndKill>(),
-                    Algorithm::Kruskal => self.generate_maze::<Kruskal>(),
-                    Algorithm::AldousBroder => self.generate_maze::<AldousBroder>(),
-                    Algorithm::Eller => self.generate_maze::<Eller>(),
-                    Algorithm::Sidewinder => self.generate_maze::<Sidewinder>(),
-                };
+        let solver_idx = match self.solvers.state.selected() {
+            Some(idx) => idx,
+            None => return,
+        };
+        let (title, solver) = &self.solvers.items[solver_idx];
+        for run in self.runs.iter_mut() {
+            let grid = match run.snapshots.get(run.generated.saturating_sub(1)) {
+                Some(snapshot) => snapshot.get_grid().clone(),
+                None => continue,
+            };
+            let (start, goal) = match (grid.positions().next(), grid.positions().last()) {
+                (Some(start), Some(goal)) => (start, goal),
+                _ => continue,
+            };
 
-                self.snapshots = Some(snapshots);
-                self.curr_algo_idx = 0;
-                self.running_algo_idx = Some(idx);
-                self.is_generator_running = true;
+            let search = solver(grid.clone(), start, goal);
+            let path = search
+                .last()
+                .and_then(|snapshot| snapshot.get_search())
+                .map(|search| search.path.to_vec());
+            match path {
+                Some(path) if !path.is_empty() => {
+                    let shortest = shortest_path(&grid, start, goal);
+                    info!(
+                        "💰 {} found a path costing {} in {} moves. The shortest path takes {} moves and costs {}",
+                        title,
+                        path_cost(&grid, &path),
+                        path.len() - 1,
+                        shortest.len().saturating_sub(1),
+                        path_cost(&grid, &shortest)
+                    );
+                }
+                _ => warn!("{} found no way to the bottom right cell", title),
             }
+            run.solve(solver_idx, search);
         }
+        self.is_generator_running = true;
     }
 
-    pub fn get_next_snapshot(&self) -> Option<&MazeSnapshot> {
-        self.get_snapshot(self.curr_algo_idx + 1)
+    pub fn stop_game(&mut self) {
+        self.game = None;
     }
 
-    pub fn get_curr_snapshot(&self) -> Option<&MazeSnapshot> {
-        self.get_snapshot(self.curr_algo_idx)
+    /// Moves the player in the direction if there's no wall in the way. The view follows the player up and down stairs
+    pub fn move_player(&mut self, player: usize, direction: Cell) {
+        let game = match self.game.as_mut() {
+            Some(game) => game,
+            None => return,
+        };
+
+        if !game.step(player, direction) {
+            return;
+        }
+
+        self.level = game.player_level(player);
+        if !game.is_won() {
+            return;
+        }
+
+        if game.players() > 1 {
+            info!(
+                "🏆 Player {} wins the race in {} moves, the shortest path takes {}. Time: {:.1?}",
+                player + 1,
+                game.moves(player),
+                game.optimal(),
+                game.elapsed()
+            );
+            return;
+        }
+
+        info!(
+            "🏆 Reached the exit in {} moves, the shortest path takes {}. Time: {:.1?}",
+            game.moves(player),
+            game.optimal(),
+            game.elapsed()
+        );
+        // races are played on one keyboard by players the scores cannot tell apart
+        let (time, moves) = (game.elapsed(), game.moves(player));
+        if let Some(maze) = self.current_maze() {
+            let score = Score {
+                maze,
+                player: scores::player_name(),
+                time,
+                moves,
+            };
+            match self.scores.record(score) {
+                Ok(rank) => info!("🥇 Ranked #{} in this maze", rank),
+                Err(err) => warn!("{}", err),
+            }
+        }
+    }
+
+    /// The maze in the first pane, the one played and ranked. It's in fog while a game in fog is played
+    pub fn current_maze(&self) -> Option<Maze> {
+        let run = self.runs.first()?;
+        let grid = run.get_curr_snapshot()?.get_grid();
+
+        let mask = match &self.mask {
+            Some(mask) => scores::fingerprint(
+                (0..mask.height()).flat_map(|y| (0..mask.width()).map(move |x| mask.is_on((x, y)) as u8)),
+            ),
+            None => String::from("none"),
+        };
+        let terrain = match &self.terrain {
+            Some(Terrain::Random) => String::from("random"),
+            Some(Terrain::Map(weights)) => scores::fingerprint(
+                (0..weights.height()).flat_map(|y| (0..weights.width()).map(move |x| weights.cost((x, y)) as u8)),
+            ),
+            None => String::from("none"),
+        };
+
+        Some(Maze {
+            algorithm: self.algorithms.items.get(run.algo_idx)?.id.to_string(),
+            topology: grid.topology(),
+            width: grid.width(),
+            height: grid.level_height(),
+            levels: grid.levels(),
+            wrap: grid.wrap(),
+            mask,
+            braid: self.braid,
+            terrain,
+            fog: self.game.as_ref().and_then(|game| game.fog()),
+            seed: run.seed,
+        })
     }
 
-    pub fn get_running_algorithm_title(&self) -> Option<&str> {
-        if let Some(idx) = self.running_algo_idx {
-            if let Some(algo) = self.algorithms.items.get(idx) {
-                return Some(algo.0);
+    /// Shows or hides the best scores in the maze of the first pane
+    pub fn toggle_high_scores(&mut self) {
+        if !self.show_scores {
+            if let Err(err) = self.scores.load() {
+                warn!("{}", err);
+                return;
             }
         }
-        None
+        self.show_scores = !self.show_scores;
     }
 
-    fn get_snapshot(&self, idx: usize) -> Option<&MazeSnapshot> {
-        if let Some(snapshosts) = &self.snapshots {
-            snapshosts.get(idx)
+    /// Switches to the next grid topology and clears the mazes drawn on the previous one
+    pub fn switch_topology(&mut self) {
+        if self.is_generator_running {
+            return;
+        }
+
+        self.topology = self.topology.next();
+        self.runs.clear();
+        info!("🔷 Switched to {} grid", self.topology);
+    }
+
+    /// Saves the mazes on the screen as SVG images to the working directory
+    pub fn export_svg(&self) {
+        if self.runs.is_empty() {
+            warn!("Nothing to export, generate a maze first");
+            return;
+        }
+
+        for run in self.runs.iter() {
+            if let (Some(snapshot), Some(title)) = (run.get_curr_snapshot(), self.get_algorithm_title(run.algo_idx)) {
+                let slug = title
+                    .split(|c: char| !c.is_ascii_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                    .collect::<Vec<_>>()
+                    .join("-")
+                    .to_lowercase();
+                let grid = snapshot.get_grid();
+
+                // every level of a stacked maze goes to a file of its own
+                for level in 0..grid.levels() {
+                    let path = match grid.levels() {
+                        1 => format!("daedalus-{}-{}.svg", slug, run.seed),
+                        _ => format!("daedalus-{}-{}-level{}.svg", slug, run.seed, level + 1),
+                    };
+                    let svg = match grid.levels() {
+                        1 => svg::render(grid),
+                        _ => svg::render(&grid.level(level)),
+                    };
+
+                    match fs::write(&path, svg) {
+                        Ok(_) => info!("💾 Saved maze to {}", path),
+                        Err(err) => error!("Cannot save maze to {}: {}", path, err),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Marks the selected algorithm for the side-by-side comparison or unmarks it if it's been marked already
+    pub fn toggle_compared_algo(&mut self) {
+        if self.is_generator_running {
+            return;
+        }
+
+        if let Some(idx) = self.algorithms.state.selected() {
+            if let Some(pos) = self.compared_algos.iter().position(|i| *i == idx) {
+                self.compared_algos.remove(pos);
+            } else if self.compared_algos.len() < MAX_COMPARED_ALGOS {
+                self.compared_algos.push(idx);
+            } else {
+                warn!("Cannot compare more than {} algorithms at once", MAX_COMPARED_ALGOS);
+            }
+        }
+    }
+
+    pub fn on_tick(&mut self) {
+        if self.algorithms.state.selected().is_none() {
+            self.algorithms.next();
+        }
+        if self.solvers.state.selected().is_none() {
+            self.solvers.next();
+        }
+
+        if !self.is_generator_running {
+            return;
+        }
+
+        let mut advanced = false;
+        for run in self.runs.iter_mut() {
+            advanced |= run.advance();
+        }
+
+        if !advanced {
+            self.is_generator_running = false;
+            for run in self.runs.iter() {
+                match run.solver_idx {
+                    Some(idx) => info!(
+                        "🔃 Performed {} iterations to solve a maze with {}",
+                        run.steps_count() - run.generated,
+                        self.solvers.items[idx].0
+                    ),
+                    None => info!(
+                        "🔃 Performed {} iterations to draw a maze with {}",
+                        run.step, self.algorithms.items[run.algo_idx].name
+                    ),
+                }
+            }
+        }
+    }
+
+    pub fn start_maze_generation(&mut self) {
+        if self.is_generator_running {
+            return;
+        }
+
+        // two or more marked algorithms are run side by side, otherwise the selected one is run alone
+        let algo_indexes = if self.compared_algos.len() > 1 {
+            self.compared_algos.clone()
+        } else if let Some(idx) = self.algorithms.state.selected() {
+            vec![idx]
         } else {
-            None
+            return;
+        };
+
+        let grid = match self.empty_grid() {
+            Ok(grid) => grid,
+            Err(err) => {
+                warn!("{}", err);
+                return;
+            }
+        };
+        if let Some(Terrain::Map(weights)) = &self.terrain {
+            if weights.width() < grid.width() || weights.height() < grid.level_height() {
+                warn!(
+                    "The weights only cover {}x{} cells of the grid, the rest cost the least",
+                    weights.width(),
+                    weights.height()
+                );
+            }
+        }
+        if grid.wrap() != self.wrap {
+            warn!(
+                "The {} grid cannot wrap around its edges as asked ({}) and wraps {} instead",
+                self.topology,
+                self.wrap,
+                grid.wrap()
+            );
+        }
+
+        let mut runs = vec![];
+        for idx in algo_indexes {
+            if let Some(descriptor) = self.algorithms.items.get(idx) {
+                if !descriptor.supports(&grid) {
+                    let masked = if grid.is_masked() { "masked " } else { "" };
+                    warn!(
+                        "{} cannot generate mazes on a {}{} grid",
+                        descriptor.name, masked, self.topology
+                    );
+                    continue;
+                }
+
+                let seed = self.seed.unwrap_or_else(rand::random);
+                let grid = match &self.terrain {
+                    Some(Terrain::Random) => {
+                        grid.clone()
+                            .weighted(&Weights::random(grid.width(), grid.level_height(), seed))
+                    }
+                    Some(Terrain::Map(weights)) => grid.clone().weighted(weights),
+                    None => grid.clone(),
+                };
+                let snapshots = self.generate_maze(descriptor, grid, seed);
+                runs.push(MazeRun::new(idx, seed, snapshots));
+            }
         }
+
+        self.runs = runs;
+        self.is_generator_running = !self.runs.is_empty();
+    }
+
+    pub fn get_algorithm_title(&self, idx: usize) -> Option<&str> {
+        self.algorithms.items.get(idx).map(|algo| algo.name)
+    }
+
+    /// The grid the mazes are carved in, shaped by the mask if there is one
+    pub fn empty_grid(&self) -> Result<Grid, MaskError> {
+        let grid = match &self.mask {
+            Some(mask) => Grid::with_mask(mask, self.topology)?,
+            None => Grid::with_topology(self.grid_width, self.grid_height, self.topology),
+        };
+        Ok(grid.stacked(self.levels).wrapped(self.wrap))
     }
 
-    fn generate_maze<T: IGenerator>(&self) -> Vec<MazeSnapshot> {
-        info!("🚀 Start maze generation");
+    fn generate_maze(&self, descriptor: &Descriptor, grid: Grid, seed: u64) -> Vec<MazeSnapshot> {
+        info!("🚀 Start maze generation with seed {}", seed);
         let start = Instant::now();
-        let maze = T::init(self.grid_width, self.grid_height).run();
+        let mut maze = descriptor.generate_with(grid, seed, &self.params);
         let duration = start.elapsed();
         info!("🏁 Finish maze generation in {:?}", duration);
+
+        if self.braid > 0.0 {
+            if let Some(perfect) = maze.last().map(|snapshot| snapshot.get_grid().clone()) {
+                let braid = Braid::new(perfect, seed, self.braid).run();
+                // every snapshot but the last one carves a passage
+                info!("🪢 Carved {} passages to braid the maze", braid.len() - 1);
+                maze.extend(braid);
+            }
+        }
         maze
     }
 }
@@ -0,0 +1,230 @@
+use super::{grid::Grid, utils::types::Pos};
+
+/// Structural metrics of a (possibly unfinished) maze
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct MazeStats {
+    pub cells: usize,
+    /// Cells with exactly one passage
+    pub dead_ends: usize,
+    /// Cells with three or more passages
+    pub junctions: usize,
+    /// Cells with two passages going in opposite directions
+    pub corridors: usize,
+    /// Cells with two passages going in perpendicular directions
+    pub turns: usize,
+    /// The number of passages on the longest path between two cells. Exact for perfect mazes
+    pub longest_path: usize,
+    /// The average number of passages between two cells that are not corridors or turns
+    pub avg_corridor_len: f64,
+    /// The share of cells that lie inside corridors or turns, i.e. where the path flows without branching
+    pub river_factor: f64,
+}
+
+impl MazeStats {
+    pub fn from_grid(grid: &Grid) -> Self {
+        let mut stats = MazeStats {
+            cells: grid.cells_count(),
+            ..Default::default()
+        };
+
+        for pos in grid.positions() {
+            match grid.carved_directions(pos)[..] {
+                [] => (),
+                [_] => stats.dead_ends += 1,
+                [a, b] if a.opposite() == b => stats.corridors += 1,
+                [_, _] => stats.turns += 1,
+                _ => stats.junctions += 1,
+            }
+        }
+
+        (stats.longest_path, _) = longest_path(grid);
+        stats.avg_corridor_len = avg_corridor_len(grid);
+        if stats.cells > 0 {
+            stats.river_factor = (stats.corridors + stats.turns) as f64 / stats.cells as f64;
+        }
+
+        stats
+    }
+
+    /// Dead ends as a percentage of all cells
+    pub fn dead_ends_pct(&self) -> f64 {
+        if self.cells == 0 {
+            return 0.0;
+        }
+        self.dead_ends as f64 * 100.0 / self.cells as f64
+    }
+}
+
+/// Computes the number of passages from the start cell to every cell reachable from it
+pub fn distances(grid: &Grid, start: Pos) -> Vec<Option<usize>> {
+    let mut distances = vec![None; grid.width() * grid.height()];
+    search(grid, start, &mut distances, &mut vec![]);
+    distances
+}
+
+/// Finds the longest path over all connected regions with two breadth-first searches per region, and counts the cells
+/// the searches visit. The searches share their buffers and only clear the cells they've reached, so every cell is
+/// visited twice and a grid of many small regions stays linear
+fn longest_path(grid: &Grid) -> (usize, usize) {
+    let mut seen = vec![false; grid.width() * grid.height()];
+    let mut distances = vec![None; grid.width() * grid.height()];
+    let mut region = vec![];
+    let (mut longest, mut visited) = (0, 0);
+
+    for pos in grid.positions() {
+        if seen[index(grid, pos)] {
+            continue;
+        }
+
+        search(grid, pos, &mut distances, &mut region);
+        visited += region.len();
+        let farthest = region.last().copied().unwrap_or(pos);
+        for pos in region.iter() {
+            seen[index(grid, *pos)] = true;
+            distances[index(grid, *pos)] = None;
+        }
+
+        search(grid, farthest, &mut distances, &mut region);
+        visited += region.len();
+        let end = region.last().copied().unwrap_or(farthest);
+        longest = longest.max(distances[index(grid, end)].unwrap_or(0));
+        for pos in region.iter() {
+            distances[index(grid, *pos)] = None;
+        }
+    }
+
+    (longest, visited)
+}
+
+/// Writes the distances of the cells reachable from the start into the buffer, which must have none of them set, and
+/// lists the cells in the order they're reached. The last one is the farthest
+fn search(grid: &Grid, start: Pos, distances: &mut [Option<usize>], region: &mut Vec<Pos>) {
+    region.clear();
+    region.push(start);
+    distances[index(grid, start)] = Some(0);
+
+    let mut next_idx = 0;
+    while let Some(&pos) = region.get(next_idx) {
+        next_idx += 1;
+        let distance = distances[index(grid, pos)].unwrap_or(0);
+        for next in grid.links(pos) {
+            let idx = index(grid, next);
+            if distances[idx].is_none() {
+                distances[idx] = Some(distance + 1);
+                region.push(next);
+            }
+        }
+    }
+}
+
+/// Walks every corridor that starts at a dead end or a junction and averages their lengths
+fn avg_corridor_len(grid: &Grid) -> f64 {
+    let is_node = |pos: Pos| grid.links(pos).len() != 2;
+    let (mut total, mut count) = (0, 0);
+
+    for pos in grid.positions().filter(|pos| is_node(*pos)) {
+        for mut next in grid.links(pos) {
+            let mut prev = pos;
+            let mut len = 1;
+
+            while !is_node(next) {
+                let forward = grid.links(next).into_iter().find(|p| *p != prev);
+                match forward {
+                    Some(forward) => {
+                        prev = next;
+                        next = forward;
+                        len += 1;
+                    }
+                    None => break,
+                }
+            }
+
+            total += len;
+            count += 1;
+        }
+    }
+
+    if count == 0 {
+        return 0.0;
+    }
+    // every corridor between two nodes has been walked from both of its ends
+    total as f64 / count as f64
+}
+
+fn index(grid: &Grid, (x, y): Pos) -> usize {
+    y * grid.width() + x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::grid::cell::Cell;
+
+    /// Carves a 3x2 maze:
+    ///  _____
+    /// |_  | |
+    /// |_____|
+    fn carve_grid() -> Grid {
+        let mut grid = Grid::new(3, 2);
+        grid.carve_passage((0, 0), Cell::EAST).unwrap();
+        grid.carve_passage((1, 0), Cell::SOUTH).unwrap();
+        grid.carve_passage((0, 1), Cell::EAST).unwrap();
+        grid.carve_passage((1, 1), Cell::EAST).unwrap();
+        grid.carve_passage((2, 1), Cell::NORTH).unwrap();
+        grid
+    }
+
+    #[test]
+    fn cell_kinds() {
+        let stats = MazeStats::from_grid(&carve_grid());
+
+        assert_eq!(stats.cells, 6);
+        assert_eq!(stats.dead_ends, 3);
+        assert_eq!(stats.junctions, 1);
+        assert_eq!(stats.corridors, 0);
+        assert_eq!(stats.turns, 2);
+        assert_eq!(stats.dead_ends_pct(), 50.0);
+    }
+
+    #[test]
+    fn longest_path_of_maze() {
+        let stats = MazeStats::from_grid(&carve_grid());
+        assert_eq!(stats.longest_path, 4);
+    }
+
+    #[test]
+    fn corridor_lengths() {
+        let stats = MazeStats::from_grid(&carve_grid());
+        // (0,0) -> (1,1): 2, (0,1) -> (1,1): 1, (2,0) -> (1,1): 2
+        assert_eq!(stats.avg_corridor_len, 5.0 / 3.0);
+        assert_eq!(stats.river_factor, 2.0 / 6.0);
+    }
+
+    #[test]
+    fn distances_from_cell() {
+        let grid = carve_grid();
+        let distances = distances(&grid, (0, 0));
+
+        assert_eq!(distances[index(&grid, (0, 0))], Some(0));
+        assert_eq!(distances[index(&grid, (1, 1))], Some(2));
+        assert_eq!(distances[index(&grid, (2, 0))], Some(4));
+    }
+
+    #[test]
+    fn grid_without_passages_stays_cheap() {
+        // every cell is a region of its own, searched once from itself and once from the farthest cell, i.e. itself
+        let grid = Grid::new(45, 45).stacked(9);
+        assert_eq!(longest_path(&grid), (0, 2 * grid.cells_count()));
+
+        assert_eq!(longest_path(&carve_grid()), (4, 2 * carve_grid().cells_count()));
+    }
+
+    #[test]
+    fn empty_grid() {
+        let stats = MazeStats::from_grid(&Grid::new(2, 2));
+
+        assert_eq!(stats.dead_ends, 0);
+        assert_eq!(stats.longest_path, 0);
+        assert_eq!(stats.avg_corridor_len, 0.0);
+    }
+}
@@ -0,0 +1,301 @@
+use std::{f64::consts::PI, fmt::Write};
+
+use super::{
+    grid::{cell::Cell, topology::Topology, Grid},
+    utils::types::Pos,
+};
+
+/// Length of a wall in pixels
+const CELL_SIZE: f64 = 20.0;
+/// Space around the maze in pixels
+const MARGIN: f64 = 10.0;
+
+type Point = (f64, f64);
+
+/// Draws the walls of the maze as an SVG image
+pub fn render(grid: &Grid) -> String {
+    let (width, height) = size(grid);
+    let mut path = String::new();
+
+    for (x, y) in grid.positions() {
+        if grid.is_cell_carved((x, y), Cell::UNDER) {
+            path += &crossing(grid, (x, y));
+            continue;
+        }
+
+        for direction in grid.directions((x, y)) {
+            if grid.is_cell_carved((x, y), direction) {
+                continue;
+            }
+
+            // a wall between two cells is drawn once, by the cell that comes first. Walls across a wrapped edge lie
+            // on both sides of the grid
+            if let Ok((nx, ny)) = grid.get_next_cell_pos((x, y), direction) {
+                if (ny, nx) < (y, x) && !grid.wraps_around((x, y), direction) {
+                    continue;
+                }
+            }
+
+            if let Some(wall) = wall(grid, (x, y), direction) {
+                path += &wall;
+            }
+        }
+    }
+
+    format!(
+        concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\">\n",
+            "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n",
+            "<path d=\"{path}\" fill=\"none\" stroke=\"black\" stroke-width=\"2\" stroke-linecap=\"round\"/>\n",
+            "</svg>\n"
+        ),
+        w = width + MARGIN * 2.0,
+        h = height + MARGIN * 2.0,
+        path = path
+    )
+}
+
+/// Size of the maze in pixels without the margins
+fn size(grid: &Grid) -> (f64, f64) {
+    let (width, height) = (grid.width() as f64, grid.height() as f64);
+
+    match grid.topology() {
+        Topology::Rectangular => (width * CELL_SIZE, height * CELL_SIZE),
+        Topology::Hexagonal => {
+            // odd columns are shifted half a cell down
+            let shift = if grid.width() > 1 { 0.5 } else { 0.0 };
+            ((width * 1.5 + 0.5) * CELL_SIZE, (height + shift) * hex_height())
+        }
+        // neighbouring triangles overlap by half of their base
+        Topology::Triangular => ((width + 1.0) * CELL_SIZE / 2.0, height * triangle_height()),
+        // the rings surround a hole as wide as a ring
+        Topology::Polar => {
+            let diameter = (height + 1.0) * CELL_SIZE * 2.0;
+            (diameter, diameter)
+        }
+    }
+}
+
+/// Returns the path drawing the wall the cell has in the direction
+fn wall(grid: &Grid, pos: Pos, direction: Cell) -> Option<String> {
+    if grid.topology() == Topology::Polar {
+        return polar_wall(grid, pos, direction);
+    }
+
+    let corners = corners(grid, pos);
+    let (from, to) = match (grid.topology(), direction) {
+        (Topology::Rectangular, Cell::NORTH) => (0, 1),
+        (Topology::Rectangular, Cell::EAST) => (1, 2),
+        (Topology::Rectangular, Cell::SOUTH) => (2, 3),
+        (Topology::Rectangular, Cell::WEST) => (3, 0),
+        (Topology::Hexagonal, Cell::SOUTH_EAST) => (0, 1),
+        (Topology::Hexagonal, Cell::SOUTH) => (1, 2),
+        (Topology::Hexagonal, Cell::SOUTH_WEST) => (2, 3),
+        (Topology::Hexagonal, Cell::NORTH_WEST) => (3, 4),
+        (Topology::Hexagonal, Cell::NORTH) => (4, 5),
+        (Topology::Hexagonal, Cell::NORTH_EAST) => (5, 0),
+        (Topology::Triangular, Cell::WEST) => (0, 1),
+        (Topology::Triangular, Cell::EAST) => (1, 2),
+        (Topology::Triangular, Cell::NORTH | Cell::SOUTH) => (2, 0),
+        _ => return None,
+    };
+    Some(line(corners[from], corners[to]))
+}
+
+/// Walls across a ring are drawn as lines, walls along a ring as arcs. The wall between a cell and the one or two
+/// cells outwards is split by the directions leading to them
+fn polar_wall(grid: &Grid, (x, y): Pos, direction: Cell) -> Option<String> {
+    let center = MARGIN + (grid.height() as f64 + 1.0) * CELL_SIZE;
+    let point = |radius: f64, angle: f64| (center + radius * angle.cos(), center + radius * angle.sin());
+
+    let cell_angle = 2.0 * PI / grid.row_width(y) as f64;
+    let (start, end) = (x as f64 * cell_angle, (x as f64 + 1.0) * cell_angle);
+    let middle = (start + end) / 2.0;
+    let (inner, outer) = ((y as f64 + 1.0) * CELL_SIZE, (y as f64 + 2.0) * CELL_SIZE);
+    let is_split = grid.directions((x, y)).contains(&Cell::SOUTH_EAST);
+
+    let wall = match direction {
+        Cell::NORTH => arc(point(inner, start), point(inner, end), inner),
+        Cell::SOUTH if is_split => arc(point(outer, start), point(outer, middle), outer),
+        Cell::SOUTH => arc(point(outer, start), point(outer, end), outer),
+        Cell::SOUTH_EAST => arc(point(outer, middle), point(outer, end), outer),
+        Cell::WEST => line(point(inner, start), point(outer, start)),
+        Cell::EAST => line(point(inner, end), point(outer, end)),
+        _ => return None,
+    };
+    Some(wall)
+}
+
+/// Draws the walls of the passage over a crossing along the cell sides, and the walls of the tunnel under it as short
+/// stubs at the corners
+fn crossing(grid: &Grid, pos: Pos) -> String {
+    let corners = corners(grid, pos);
+    let inset = CELL_SIZE / 4.0;
+    let (left, top) = corners[0];
+    let (right, bottom) = corners[2];
+
+    if grid.is_cell_carved(pos, Cell::EAST) {
+        [
+            line((left, top + inset), (right, top + inset)),
+            line((left, bottom - inset), (right, bottom - inset)),
+            line((left, top), (left, top + inset)),
+            line((right, top), (right, top + inset)),
+            line((left, bottom - inset), (left, bottom)),
+            line((right, bottom - inset), (right, bottom)),
+        ]
+        .concat()
+    } else {
+        [
+            line((left + inset, top), (left + inset, bottom)),
+            line((right - inset, top), (right - inset, bottom)),
+            line((left, top), (left + inset, top)),
+            line((left, bottom), (left + inset, bottom)),
+            line((right - inset, top), (right, top)),
+            line((right - inset, bottom), (right, bottom)),
+        ]
+        .concat()
+    }
+}
+
+fn line((x1, y1): Point, (x2, y2): Point) -> String {
+    let mut path = String::new();
+    write!(path, "M{:.1} {:.1}L{:.1} {:.1}", x1, y1, x2, y2).unwrap();
+    path
+}
+
+/// A clockwise arc of the circle with the radius
+fn arc((x1, y1): Point, (x2, y2): Point, radius: f64) -> String {
+    let mut path = String::new();
+    write!(
+        path,
+        "M{:.1} {:.1}A{:.1} {:.1} 0 0 1 {:.1} {:.1}",
+        x1, y1, radius, radius, x2, y2
+    )
+    .unwrap();
+    path
+}
+
+/// Corners of the cell. Rectangles go clockwise from the top left corner, hexagons from the right one. Triangles go
+/// from the left end of the base over the apex, so their west side always comes first
+fn corners(grid: &Grid, pos: Pos) -> Vec<Point> {
+    let (x, y) = (pos.0 as f64, pos.1 as f64);
+
+    match grid.topology() {
+        // polar walls are curved, see `polar_wall`
+        Topology::Polar => vec![],
+        Topology::Rectangular => {
+            let (left, top) = (MARGIN + x * CELL_SIZE, MARGIN + y * CELL_SIZE);
+            let (right, bottom) = (left + CELL_SIZE, top + CELL_SIZE);
+            vec![(left, top), (right, top), (right, bottom), (left, bottom)]
+        }
+        Topology::Hexagonal => {
+            let half_height = hex_height() / 2.0;
+            let cx = MARGIN + CELL_SIZE + x * CELL_SIZE * 1.5;
+            let cy = MARGIN + half_height * (1.0 + y * 2.0 + x % 2.0);
+            vec![
+                (cx + CELL_SIZE, cy),
+                (cx + CELL_SIZE / 2.0, cy + half_height),
+                (cx - CELL_SIZE / 2.0, cy + half_height),
+                (cx - CELL_SIZE, cy),
+                (cx - CELL_SIZE / 2.0, cy - half_height),
+                (cx + CELL_SIZE / 2.0, cy - half_height),
+            ]
+        }
+        Topology::Triangular => {
+            let (left, top) = (MARGIN + x * CELL_SIZE / 2.0, MARGIN + y * triangle_height());
+            let (right, bottom) = (left + CELL_SIZE, top + triangle_height());
+            let apex = left + CELL_SIZE / 2.0;
+
+            if Topology::is_pointing_up(pos) {
+                vec![(left, bottom), (apex, top), (right, bottom)]
+            } else {
+                vec![(left, top), (apex, bottom), (right, top)]
+            }
+        }
+    }
+}
+
+/// Distance between the top and bottom walls of a hexagon
+fn hex_height() -> f64 {
+    CELL_SIZE * 3f64.sqrt()
+}
+
+fn triangle_height() -> f64 {
+    hex_height() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::grid::wrap::Wrap;
+
+    fn walls_count(svg: &str) -> usize {
+        svg.matches('M').count()
+    }
+
+    #[test]
+    fn shared_walls_are_drawn_once() {
+        let mut grid = Grid::new(2, 1);
+        assert_eq!(walls_count(&render(&grid)), 7);
+
+        grid.carve_passage((0, 0), Cell::EAST).unwrap();
+        assert_eq!(walls_count(&render(&grid)), 6);
+    }
+
+    #[test]
+    fn wrapped_walls_are_drawn_on_both_edges() {
+        let mut grid = Grid::new(3, 1).wrapped(Wrap::Horizontal);
+        assert_eq!(walls_count(&render(&grid)), 10);
+
+        // the passage opens the west edge of the first cell and the east edge of the last one
+        grid.carve_passage((2, 0), Cell::EAST).unwrap();
+        assert_eq!(walls_count(&render(&grid)), 8);
+    }
+
+    #[test]
+    fn crossings_have_bridges() {
+        let mut grid = Grid::new(3, 3);
+        grid.add_crossing((1, 1), Cell::EAST).unwrap();
+
+        // the passages over and under the crossing open its 4 sides, the bridge and the tunnel add 6 walls
+        assert_eq!(walls_count(&render(&grid)), 24 - 4 + 6);
+    }
+
+    #[test]
+    fn hexagonal_cells() {
+        assert_eq!(walls_count(&render(&Grid::with_topology(1, 1, Topology::Hexagonal))), 6);
+        // the second cell is half a cell lower and shares the south-east wall of the first one
+        assert_eq!(
+            walls_count(&render(&Grid::with_topology(2, 1, Topology::Hexagonal))),
+            11
+        );
+    }
+
+    #[test]
+    fn triangles() {
+        assert_eq!(
+            walls_count(&render(&Grid::with_topology(1, 1, Topology::Triangular))),
+            3
+        );
+        // the top-left triangle shares its east side and its base, the bottom-left one its east side
+        assert_eq!(
+            walls_count(&render(&Grid::with_topology(2, 2, Topology::Triangular))),
+            9
+        );
+    }
+
+    #[test]
+    fn polar_rings() {
+        // the first ring has 6 arcs inwards, 6 lines across and 12 arcs outwards since the second ring splits its
+        // cells, the second ring has 12 lines across and 12 arcs on the outside
+        let svg = render(&Grid::with_topology(1, 2, Topology::Polar));
+        assert_eq!(walls_count(&svg), 6 + 6 + 12 + 12 + 12);
+        assert_eq!(svg.matches('A').count(), 6 + 12 + 12);
+    }
+
+    #[test]
+    fn image_fits_the_maze() {
+        let svg = render(&Grid::new(3, 2));
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"80\" height=\"60\""));
+    }
+}
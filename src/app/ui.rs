@@ -1,16 +1,22 @@
+use std::time::Duration;
+
 use tui::{
     backend::Backend,
     layout::{Constraint, Direction, Layout, Rect, Alignment},
     style::{Color, Modifier, Style},
     text::{Span, Spans},
-    widgets::{Block, Borders, List, ListItem, Table, Cell, Row, BorderType, Paragraph},
+    widgets::{Block, Borders, List, ListItem, Table, Cell, Row, BorderType, Paragraph, Clear, Wrap},
     Frame,
 };
 
-use crate::app::{widgets::maze_container::MazeContainer, App};
+use crate::app::{
+    state::SharedVec,
+    widgets::maze_container::{MazeContainer, HIGHLIGHT_COLOR, SET_COLORS},
+    App,
+};
 use tui_logger::TuiLoggerWidget;
 
-use super::{actions::Actions, grid::Grid};
+use super::{actions::Actions, game::Game, grid::Grid, scores::TOP_SCORES, stats::MazeStats};
 
 pub fn draw<B: Backend>(rect: &mut Frame<B>, app: &mut App) {
     let chunks = Layout::default()
@@ -35,25 +41,14 @@ where
         .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
         .split(area);
 
-    if let Some(snapshot) = app.state.get_curr_snapshot() {
-        if let Some(title) = app.state.get_running_algorithm_title() {
-            let maze_container = MazeContainer::new(snapshot.get_grid(), snapshot.get_highlights())
-                .block(Block::default().title(title).borders(Borders::ALL));
-
-            f.render_widget(maze_container, chunks[0]);
-        }
-    } else {
-        let grid = &Grid::new(app.state.grid_width, app.state.grid_height);
-        let highlights = vec![];
-        let maze_container =
-            MazeContainer::new(&grid, &highlights).block(Block::default().title("Maze").borders(Borders::ALL));
-
-        f.render_widget(maze_container, chunks[0]);
+    draw_mazes(f, app, chunks[0]);
+    if app.state.show_scores {
+        draw_high_scores(f, app, chunks[0]);
     }
 
     let dashboard_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Min(20), Constraint::Percentage(100)])
+        .constraints([Constraint::Min(20), Constraint::Length(6), Constraint::Percentage(100)])
         .split(chunks[1]);
 
     // Iterate through all elements in the `items` app and append some debug text to it.
@@ -62,8 +57,14 @@ where
         .algorithms
         .items
         .iter()
-        .map(|i| {
-            let lines = vec![Spans::from(i.0)];
+        .enumerate()
+        .map(|(idx, i)| {
+            // show the pane number of the algorithms marked for comparison
+            let title = match app.state.compared_algos.iter().position(|c| *c == idx) {
+                Some(pane) => format!("{} [{}]", i.name, pane + 1),
+                None => i.name.to_string(),
+            };
+            let lines = vec![Spans::from(title)];
             ListItem::new(lines).style(Style::default().fg(Color::White))
         })
         .collect();
@@ -83,16 +84,305 @@ where
         .highlight_style(Style::default().fg(text_color).add_modifier(Modifier::BOLD))
         .highlight_symbol("> ");
 
+    let menu_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Min(0),
+                Constraint::Length(app.state.solvers.items.len() as u16 + 2),
+            ]
+            .as_ref(),
+        )
+        .split(control_panel_chunks[0]);
+
     // We can now render the item list
-    f.render_stateful_widget(items, control_panel_chunks[0], &mut app.state.algorithms.state);
+    f.render_stateful_widget(items, menu_chunks[0], &mut app.state.algorithms.state);
+
+    // The solvers are listed below the algorithms, the ones added with the app follow the built-in ones
+    let solvers: Vec<ListItem> = app
+        .state
+        .solvers
+        .items
+        .iter()
+        .map(|(title, _)| ListItem::new(vec![Spans::from(*title)]).style(Style::default().fg(Color::White)))
+        .collect();
+    let solvers = List::new(solvers)
+        .block(Block::default().borders(Borders::ALL).title("Solver"))
+        .highlight_style(Style::default().fg(text_color).add_modifier(Modifier::BOLD))
+        .highlight_symbol("> ");
+    f.render_stateful_widget(solvers, menu_chunks[1], &mut app.state.solvers.state);
 
     // Draw the help block
     let help = draw_help(&app.actions);
     f.render_widget(help, control_panel_chunks[1]);
 
+    // Draw the stats of the first maze on the screen
+    let stats = draw_stats(app);
+    f.render_widget(stats, dashboard_chunks[1]);
+
+    // The highlighted algorithm is described next to the logs
+    let bottom_chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(dashboard_chunks[2]);
+
+    let about = draw_about(app);
+    f.render_widget(about, bottom_chunks[0]);
+
     // Render logs
     let logs = draw_logs();
-    f.render_widget(logs, dashboard_chunks[1]);
+    f.render_widget(logs, bottom_chunks[1]);
+}
+
+fn draw_mazes<B>(f: &mut Frame<B>, app: &App, area: Rect)
+where
+    B: Backend,
+{
+    if let Some(game) = &app.state.game {
+        draw_game(f, app, game, area);
+        return;
+    }
+
+    if app.state.runs.is_empty() {
+        let grid = &app
+            .state
+            .empty_grid()
+            .map(|grid| grid.level(app.state.level))
+            .unwrap_or_else(|_| Grid::with_topology(app.state.grid_width, app.state.grid_height, app.state.topology));
+        let highlights = SharedVec::default();
+        let maze_container =
+            MazeContainer::new(grid, &highlights).block(Block::default().title("Maze").borders(Borders::ALL));
+
+        f.render_widget(maze_container, area);
+        return;
+    }
+
+    let areas = split_maze_area(area, app.state.runs.len());
+    for (run, area) in app.state.runs.iter().zip(areas) {
+        if let (Some(snapshot), Some(title)) = (run.get_curr_snapshot(), app.state.get_algorithm_title(run.algo_idx)) {
+            let mut title = format!("{} · step {}/{}", title, run.step + 1, run.steps_count());
+            if let Some((solver, _)) = run.solver_idx.and_then(|idx| app.state.solvers.items.get(idx)) {
+                title += &format!(" · solved with {}", solver);
+            }
+
+            // stacked mazes are shown one level at a time
+            let levels = snapshot.get_grid().levels();
+            let level_snapshot;
+            let snapshot = if levels > 1 {
+                let level = app.state.level.min(levels - 1);
+                title += &format!(" · level {}/{}", level + 1, levels);
+                level_snapshot = snapshot.level(level);
+                &level_snapshot
+            } else {
+                snapshot
+            };
+
+            let mut maze_container = MazeContainer::new(snapshot.get_grid(), snapshot.get_highlights())
+                .block(Block::default().title(title).borders(Borders::ALL))
+                .search(snapshot.get_search());
+
+            if app.state.show_sets {
+                maze_container = maze_container.sets(snapshot.get_sets());
+            }
+
+            f.render_widget(maze_container, area);
+        }
+    }
+}
+
+/// Draws the maze of the first pane with the player in it, and the results over it once the exit is reached
+fn draw_game<B>(f: &mut Frame<B>, app: &App, game: &Game, area: Rect)
+where
+    B: Backend,
+{
+    let run = match app.state.runs.first() {
+        Some(run) => run,
+        None => return,
+    };
+    let (snapshot, levels) = match run.get_curr_snapshot() {
+        Some(snapshot) => (snapshot.level(app.state.level), snapshot.get_grid().levels()),
+        None => return,
+    };
+
+    let elapsed = format_duration(game.elapsed());
+    let mode = match (game.fog(), game.players()) {
+        (Some(fog), _) => format!("Play in the fog ({})", fog),
+        (None, 1) => String::from("Play"),
+        (None, _) => String::from("Race"),
+    };
+    let moves = (0..game.players())
+        .map(|player| game.moves(player).to_string())
+        .collect::<Vec<_>>();
+    let mut title = format!(
+        "{} · {} · moves {} · {}",
+        mode,
+        app.state.get_algorithm_title(run.algo_idx).unwrap_or_default(),
+        moves.join(" vs "),
+        elapsed
+    );
+    if levels > 1 {
+        title += &format!(" · level {}/{}", app.state.level + 1, levels);
+    }
+
+    let view = game.view(app.state.level);
+    let highlights = SharedVec::default();
+    let maze_container = MazeContainer::new(snapshot.get_grid(), &highlights)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .game(&view);
+    f.render_widget(maze_container, area);
+
+    if !game.is_won() {
+        return;
+    }
+
+    let headline = match game.winner() {
+        Some(winner) if game.players() > 1 => format!("Player {} wins!", winner + 1),
+        _ => String::from("You reached the exit!"),
+    };
+    let mut text = vec![
+        Spans::from(Span::styled(
+            headline,
+            Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(""),
+    ];
+    if game.players() > 1 {
+        text.extend(
+            (0..game.players())
+                .map(|player| Spans::from(format!("Player {} moves: {}", player + 1, game.moves(player)))),
+        );
+    } else {
+        text.push(Spans::from(format!("Moves: {}", game.moves(0))));
+    }
+    text.extend(vec![
+        Spans::from(format!("Shortest path: {}", game.optimal())),
+        Spans::from(format!("Time: {}", elapsed)),
+        Spans::from(format!("Seed: {}", run.seed)),
+        Spans::from(""),
+        Spans::from(Span::styled(
+            "Press h for high scores",
+            Style::default().fg(Color::Gray),
+        )),
+        Spans::from(Span::styled("Press Esc to go back", Style::default().fg(Color::Gray))),
+    ]);
+    let popup = centered_rect(32, text.len() as u16 + 2, area);
+    let results = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .block(Block::default().title("Results").borders(Borders::ALL));
+
+    f.render_widget(Clear, popup);
+    f.render_widget(results, popup);
+}
+
+/// Lists the best scores in the maze of the first pane over the mazes
+fn draw_high_scores<B>(f: &mut Frame<B>, app: &App, area: Rect)
+where
+    B: Backend,
+{
+    let header_style = Style::default().fg(Color::LightCyan);
+    let value_style = Style::default().fg(Color::Gray);
+
+    let (title, rows) = match app.state.current_maze() {
+        Some(maze) => {
+            let rows = app
+                .state
+                .scores
+                .top(&maze)
+                .into_iter()
+                .take(TOP_SCORES)
+                .enumerate()
+                .map(|(idx, score)| {
+                    Row::new(vec![
+                        Cell::from(Span::styled(format!("#{}", idx + 1), value_style)),
+                        Cell::from(Span::styled(score.player.clone(), value_style)),
+                        Cell::from(Span::styled(format_duration(score.time), value_style)),
+                        Cell::from(Span::styled(score.moves.to_string(), value_style)),
+                    ])
+                })
+                .collect::<Vec<_>>();
+            // the scores are kept by the id of the generator, the panel shows its title
+            let title = app
+                .state
+                .algorithms
+                .items
+                .iter()
+                .find(|descriptor| descriptor.id == maze.algorithm)
+                .map_or(maze.algorithm.as_str(), |descriptor| descriptor.name);
+            (format!("High scores · {} · {}", title, maze), rows)
+        }
+        None => (String::from("High scores"), vec![]),
+    };
+
+    let note = match (app.state.runs.is_empty(), rows.is_empty()) {
+        (true, _) => Some(String::from("Generate a maze to see its scores")),
+        (false, true) => match app.state.scores.path() {
+            Some(path) => Some(format!("No scores yet in {}", path.display())),
+            None => Some(String::from("No scores yet, and no data directory to save them in")),
+        },
+        (false, false) => None,
+    };
+    let height = rows.len().max(1) as u16 + 3;
+    let popup = centered_rect(area.width.saturating_sub(4).min(60), height, area);
+    let block = Block::default().title(title).borders(Borders::ALL);
+
+    f.render_widget(Clear, popup);
+    if let Some(note) = note {
+        f.render_widget(Paragraph::new(note).style(value_style).block(block), popup);
+        return;
+    }
+
+    let header =
+        Row::new(["Rank", "Player", "Time", "Moves"].map(|label| Cell::from(Span::styled(label, header_style))));
+    let table = Table::new(rows)
+        .header(header)
+        .block(block)
+        .widths(&[
+            Constraint::Length(6),
+            Constraint::Min(20),
+            Constraint::Length(8),
+            Constraint::Length(6),
+        ])
+        .column_spacing(1);
+    f.render_widget(table, popup);
+}
+
+/// Places a rectangle of the size in the middle of the area, cut to fit it
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let (width, height) = (width.min(area.width), height.min(area.height));
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Splits the maze area into panes: one, two side by side or a 2x2 grid for three and four
+fn split_maze_area(area: Rect, count: usize) -> Vec<Rect> {
+    let halves = [Constraint::Percentage(50), Constraint::Percentage(50)];
+    let split_columns = |area: Rect| {
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(halves.as_ref())
+            .split(area)
+    };
+
+    match count {
+        0 | 1 => vec![area],
+        2 => split_columns(area),
+        _ => Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(halves.as_ref())
+            .split(area)
+            .into_iter()
+            .flat_map(split_columns)
+            .collect(),
+    }
 }
 
 fn draw_title<'a>(app: &'a App) -> Paragraph<'a> {
@@ -107,6 +397,142 @@ fn draw_title<'a>(app: &'a App) -> Paragraph<'a> {
         )
 }
 
+fn draw_stats<'a>(app: &App) -> Table<'a> {
+    let label_style = Style::default().fg(Color::LightCyan);
+    let value_style = Style::default().fg(Color::Gray);
+
+    let (title, stats, step) = match app.state.runs.first() {
+        Some(run) => {
+            let title = app.state.get_algorithm_title(run.algo_idx).unwrap_or_default();
+            let stats = run.get_stats().clone();
+            (
+                format!("Stats · {}", title),
+                stats,
+                format!("{}/{}", run.step + 1, run.steps_count()),
+            )
+        }
+        None => (String::from("Stats"), MazeStats::default(), String::from("-")),
+    };
+
+    let cells = [
+        ("Step", step, "Longest path", stats.longest_path.to_string()),
+        (
+            "Dead ends",
+            format!("{} ({:.1}%)", stats.dead_ends, stats.dead_ends_pct()),
+            "Junctions",
+            stats.junctions.to_string(),
+        ),
+        (
+            "Corridors",
+            stats.corridors.to_string(),
+            "Turns",
+            stats.turns.to_string(),
+        ),
+        (
+            "Avg corridor length",
+            format!("{:.2}", stats.avg_corridor_len),
+            "River factor",
+            format!("{:.2}", stats.river_factor),
+        ),
+    ];
+
+    let rows = cells.into_iter().map(|(label1, value1, label2, value2)| {
+        Row::new(vec![
+            Cell::from(Span::styled(label1, label_style)),
+            Cell::from(Span::styled(value1, value_style)),
+            Cell::from(Span::styled(label2, label_style)),
+            Cell::from(Span::styled(value2, value_style)),
+        ])
+    });
+
+    Table::new(rows)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Plain)
+                .title(title),
+        )
+        .widths(&[
+            Constraint::Length(20),
+            Constraint::Length(15),
+            Constraint::Length(15),
+            Constraint::Min(10),
+        ])
+        .column_spacing(1)
+}
+
+/// Describes the algorithm highlighted in the menu with what its generator tells about itself
+fn draw_about<'a>(app: &App) -> Paragraph<'a> {
+    let label_style = Style::default().fg(Color::LightCyan);
+    let text_style = Style::default().fg(Color::Gray);
+
+    let descriptor = app
+        .state
+        .algorithms
+        .state
+        .selected()
+        .and_then(|idx| app.state.algorithms.items.get(idx));
+    let descriptor = match descriptor {
+        Some(descriptor) => descriptor,
+        None => return Paragraph::new("").block(Block::default().borders(Borders::ALL).title("About")),
+    };
+    let about = descriptor.about;
+    let field = |label: &str, value: String| {
+        Spans::from(vec![
+            Span::styled(format!("{}: ", label), label_style),
+            Span::styled(value, text_style),
+        ])
+    };
+
+    let mut lines = vec![
+        Spans::from(Span::styled(about.description, text_style)),
+        Spans::from(""),
+        Spans::from(Span::styled("Pseudocode", label_style)),
+    ];
+    lines.extend(
+        about
+            .pseudocode
+            .iter()
+            .map(|line| Spans::from(Span::styled(format!("  {}", line), text_style))),
+    );
+    lines.push(Spans::from(""));
+    lines.push(field("Bias", about.bias.to_string()));
+    lines.push(field("Time", about.time.to_string()));
+    lines.push(field("Memory", about.memory.to_string()));
+    for param in descriptor.parameters {
+        lines.push(field(
+            &format!("--param {}", param.name),
+            format!(
+                "{}, from {} to {} ({} by default)",
+                param.description, param.min, param.max, param.default
+            ),
+        ));
+    }
+
+    // what the colors of the cells mean while the maze is being generated
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(vec![
+        Span::styled("  ", Style::default().bg(HIGHLIGHT_COLOR)),
+        Span::styled(format!(" {}", about.highlights), text_style),
+    ]));
+    if let Some(sets) = about.sets {
+        let mut spans = SET_COLORS[..3]
+            .iter()
+            .map(|color| Span::styled(" ", Style::default().bg(*color)))
+            .collect::<Vec<_>>();
+        spans.push(Span::styled(format!(" {}, shown with c", sets), text_style));
+        lines.push(Spans::from(spans));
+    }
+
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("About · {}", descriptor.name)),
+        )
+        .wrap(Wrap { trim: false })
+}
+
 fn draw_logs<'a>() -> TuiLoggerWidget<'a> {
     TuiLoggerWidget::default()
         .style_error(Style::default().fg(Color::Red))
@@ -123,7 +549,7 @@ fn draw_logs<'a>() -> TuiLoggerWidget<'a> {
         .style(Style::default().fg(Color::White))
 }
 
-fn draw_help(actions: &Actions) -> Table {
+fn draw_help(actions: &Actions) -> Table<'_> {
     let key_style = Style::default().fg(Color::LightCyan);
     let help_style = Style::default().fg(Color::Gray);
 
@@ -1,65 +1,127 @@
+/// Disjoint sets of nodes (union-find). Every set is a tree of nodes referencing their parents, identified by the
+/// root node. Lookups compress the paths to the root and unions attach the smaller tree under the bigger one, so
+/// the trees stay almost flat
 pub struct ArenaTree {
     nodes: Vec<Node>,
+    sets_count: usize,
 }
 
 struct Node {
-    parent: Option<NodeId>,
+    parent: NodeId,
+    /// The number of nodes in the tree. Only up to date for roots
+    size: usize,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct NodeId(pub usize);
 
 impl ArenaTree {
     pub fn new() -> ArenaTree {
-        ArenaTree { nodes: vec![] }
+        ArenaTree {
+            nodes: vec![],
+            sets_count: 0,
+        }
+    }
+
+    /// Creates an arena of `count` nodes, each in its own set
+    pub fn with_nodes(count: usize) -> ArenaTree {
+        let mut arena = ArenaTree::new();
+        for _ in 0..count {
+            arena.new_node();
+        }
+        arena
+    }
+
+    /// Puts every node back in a set of its own, so the arena can be reused for new sets of the same nodes
+    pub fn reset(&mut self) {
+        for (idx, node) in self.nodes.iter_mut().enumerate() {
+            node.parent = NodeId(idx);
+            node.size = 1;
+        }
+        self.sets_count = self.nodes.len();
     }
 
     pub fn new_node(&mut self) -> NodeId {
-        let next_idx = self.nodes.len();
-        self.nodes.push(Node { parent: None });
-        NodeId(next_idx)
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(Node { parent: id, size: 1 });
+        self.sets_count += 1;
+        id
     }
 
-    pub fn connect(&mut self, id1: NodeId, id2: NodeId) {
-        let root1 = self.root(id1);
-        let root2 = self.root(id2);
+    /// Merges the sets of two nodes and returns the root of the merged set
+    pub fn connect(&mut self, id1: NodeId, id2: NodeId) -> Option<NodeId> {
+        let root1 = self.root(id1)?;
+        let root2 = self.root(id2)?;
 
-        if root1.and(root2).is_none() {
-            return;
+        if root1 == root2 {
+            return Some(root1);
         }
 
-        if let Some(node) = self.nodes.get_mut(root2.unwrap().0) {
-            node.parent = root1;
+        let (root, child) = if self.nodes[root1.0].size >= self.nodes[root2.0].size {
+            (root1, root2)
+        } else {
+            (root2, root1)
+        };
+
+        self.nodes[child.0].parent = root;
+        self.nodes[root.0].size += self.nodes[child.0].size;
+        self.sets_count -= 1;
+
+        Some(root)
+    }
+
+    pub fn connected(&mut self, id1: NodeId, id2: NodeId) -> bool {
+        match (self.root(id1), self.root(id2)) {
+            (Some(root1), Some(root2)) => root1 == root2,
+            _ => false,
         }
     }
 
-    pub fn connected(&self, id1: NodeId, id2: NodeId) -> bool {
-        let root1 = self.root(id1);
-        let root2 = self.root(id2);
+    /// Finds the root of the node's set, pointing every node on the way directly to the root
+    pub fn root(&mut self, id: NodeId) -> Option<NodeId> {
+        self.nodes.get(id.0)?;
+
+        let mut root = id;
+        while self.nodes[root.0].parent != root {
+            root = self.nodes[root.0].parent;
+        }
 
-        if root1.and(root2).is_none() {
-            return false;
+        let mut node = id;
+        while node != root {
+            let parent = self.nodes[node.0].parent;
+            self.nodes[node.0].parent = root;
+            node = parent;
         }
 
-        root1.unwrap().0 == root2.unwrap().0
+        Some(root)
     }
 
-    fn root(&self, id: NodeId) -> Option<NodeId> {
-        let node = self.nodes.get(id.0);
-        if node.is_none() {
-            return None;
-        }
+    pub fn sets_count(&self) -> usize {
+        self.sets_count
+    }
 
-        if let Some(parent) = node.unwrap().parent {
-            self.root(parent)
-        } else {
-            Some(id)
+    /// Lists the nodes of every set. Sets are ordered by their smallest node, nodes are ordered within a set
+    pub fn sets(&mut self) -> Vec<Vec<NodeId>> {
+        let mut set_indexes: Vec<Option<usize>> = vec![None; self.nodes.len()];
+        let mut sets: Vec<Vec<NodeId>> = vec![];
+
+        for idx in 0..self.nodes.len() {
+            let id = NodeId(idx);
+            let root = self.root(id).unwrap();
+
+            match set_indexes[root.0] {
+                Some(set_idx) => sets[set_idx].push(id),
+                None => {
+                    set_indexes[root.0] = Some(sets.len());
+                    sets.push(vec![id]);
+                }
+            }
         }
+
+        sets
     }
 }
 
-impl Node {}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -72,8 +134,8 @@ mod tests {
         let node2 = arena.new_node();
         let node3 = arena.new_node();
 
-        assert_eq!(arena.connected(node1, node2), false);
-        assert_eq!(arena.connected(node1, node3), false);
+        assert!(!arena.connected(node1, node2));
+        assert!(!arena.connected(node1, node3));
     }
 
     #[test]
@@ -85,8 +147,8 @@ mod tests {
         let node3 = arena.new_node();
 
         arena.connect(node1, node2);
-        assert_eq!(arena.connected(node1, node2), true);
-        assert_eq!(arena.connected(node1, node3), false);
+        assert!(arena.connected(node1, node2));
+        assert!(!arena.connected(node1, node3));
     }
 
     #[test]
@@ -100,9 +162,9 @@ mod tests {
         arena.connect(node1, node2);
         arena.connect(node3, node2);
 
-        assert_eq!(arena.connected(node1, node2), true);
-        assert_eq!(arena.connected(node1, node3), true);
-        assert_eq!(arena.connected(node3, node2), true);
+        assert!(arena.connected(node1, node2));
+        assert!(arena.connected(node1, node3));
+        assert!(arena.connected(node3, node2));
     }
 
     #[test]
@@ -119,4 +181,75 @@ mod tests {
         let mut arena = ArenaTree::new();
         arena.connect(NodeId(1), NodeId(2));
     }
+
+    #[test]
+    fn smaller_set_is_attached_to_bigger_one() {
+        let mut arena = ArenaTree::with_nodes(4);
+
+        arena.connect(NodeId(1), NodeId(2));
+        let root = arena.connect(NodeId(0), NodeId(1));
+
+        assert_eq!(root, arena.root(NodeId(1)));
+        assert_ne!(root, Some(NodeId(0)));
+        assert_eq!(arena.root(NodeId(0)), root);
+    }
+
+    #[test]
+    fn sets_count() {
+        let mut arena = ArenaTree::with_nodes(5);
+        assert_eq!(arena.sets_count(), 5);
+
+        arena.connect(NodeId(0), NodeId(1));
+        arena.connect(NodeId(3), NodeId(4));
+        arena.connect(NodeId(1), NodeId(0));
+        assert_eq!(arena.sets_count(), 3);
+
+        arena.connect(NodeId(4), NodeId(0));
+        assert_eq!(arena.sets_count(), 2);
+    }
+
+    #[test]
+    fn reset_splits_every_set() {
+        let mut arena = ArenaTree::with_nodes(4);
+        arena.connect(NodeId(0), NodeId(1));
+        arena.connect(NodeId(2), NodeId(1));
+
+        arena.reset();
+        assert_eq!(arena.sets_count(), 4);
+        assert!(!arena.connected(NodeId(0), NodeId(1)));
+
+        arena.connect(NodeId(3), NodeId(2));
+        assert_eq!(
+            arena.sets(),
+            vec![vec![NodeId(0)], vec![NodeId(1)], vec![NodeId(2), NodeId(3)]]
+        );
+    }
+
+    #[test]
+    fn list_sets() {
+        let mut arena = ArenaTree::with_nodes(5);
+        arena.connect(NodeId(4), NodeId(1));
+        arena.connect(NodeId(2), NodeId(0));
+
+        assert_eq!(
+            arena.sets(),
+            vec![vec![NodeId(0), NodeId(2)], vec![NodeId(1), NodeId(4)], vec![NodeId(3)]]
+        );
+    }
+
+    #[test]
+    fn long_chain_does_not_overflow_stack() {
+        let count = 1_000_000;
+        let mut arena = ArenaTree::with_nodes(count);
+
+        // unions by size never build a chain, so every node is hung under the previous one by hand
+        for idx in 1..count {
+            arena.nodes[idx].parent = NodeId(idx - 1);
+        }
+        arena.nodes[0].size = count;
+        arena.sets_count = 1;
+
+        assert_eq!(arena.root(NodeId(count - 1)), Some(NodeId(0)));
+        assert!(arena.nodes.iter().all(|node| node.parent == NodeId(0)));
+    }
 }
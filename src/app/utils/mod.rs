@@ -1,3 +1,4 @@
 pub mod arena;
 pub mod types;
 pub mod random;
+pub mod shared_vec;
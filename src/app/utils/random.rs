@@ -1,10 +1,17 @@
 use rand::Rng;
 
 use super::types::Pos;
+use crate::app::grid::Grid;
 
-pub fn get_start_pos(max_x: usize, max_y: usize) -> Pos {
-    let mut rng = rand::thread_rng();
-    let y = rng.gen_range(0..max_y);
-    let x = rng.gen_range(0..max_x);
-    (x, y)
+/// Picks a random row and then a random cell in it, or any cell if a mask switched that one off
+pub fn get_start_pos<R: Rng>(rng: &mut R, grid: &Grid) -> Pos {
+    let y = rng.gen_range(0..grid.height());
+    let x = rng.gen_range(0..grid.row_width(y));
+    if grid.contains((x, y)) {
+        return (x, y);
+    }
+
+    // a mask switched the cell off, so draw from the cells that are left
+    let positions = grid.positions().collect::<Vec<_>>();
+    positions[rng.gen_range(0..positions.len())]
 }
@@ -0,0 +1,342 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut, Range},
+    sync::Arc,
+};
+
+/// Bits of an index that pick a child of a node, every node holds up to 16 children or items
+const BITS: usize = 4;
+const CHUNK: usize = 1 << BITS;
+const MASK: usize = CHUNK - 1;
+
+/// A vector whose clones share their items until they're written. The items are kept in a tree of small chunks and a
+/// write copies only the chunk of the item and the nodes above it, so a snapshot taken after a carved passage costs a
+/// few chunks instead of the whole grid
+pub struct SharedVec<T> {
+    len: usize,
+    /// The bits of an index above the ones that pick an item in a chunk
+    shift: usize,
+    root: Arc<Node<T>>,
+}
+
+#[derive(Clone)]
+enum Node<T> {
+    Branch(Vec<Arc<Node<T>>>),
+    Leaf(Vec<T>),
+}
+
+impl<T: Clone> SharedVec<T> {
+    /// A vector of `len` copies of the value
+    pub fn from_elem(value: T, len: usize) -> SharedVec<T> {
+        SharedVec::from(vec![value; len])
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
+        if idx >= self.len {
+            return None;
+        }
+
+        let (mut node, mut shift) = (Arc::make_mut(&mut self.root), self.shift);
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = Arc::make_mut(&mut children[(idx >> shift) & MASK]);
+                    shift -= BITS;
+                }
+                Node::Leaf(items) => return items.get_mut(idx & MASK),
+            }
+        }
+    }
+
+    /// Appends an item, adding a level above the root once every chunk below it is full
+    pub fn push(&mut self, value: T) {
+        if self.len == CHUNK << self.shift {
+            let root = std::mem::replace(&mut self.root, Arc::new(Node::Branch(vec![])));
+            self.root = Arc::new(Node::Branch(vec![root]));
+            self.shift += BITS;
+        }
+
+        let (mut node, mut shift, idx) = (Arc::make_mut(&mut self.root), self.shift, self.len);
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    let child = (idx >> shift) & MASK;
+                    if child == children.len() {
+                        let empty = if shift == BITS {
+                            Node::Leaf(vec![])
+                        } else {
+                            Node::Branch(vec![])
+                        };
+                        children.push(Arc::new(empty));
+                    }
+                    node = Arc::make_mut(&mut children[child]);
+                    shift -= BITS;
+                }
+                Node::Leaf(items) => {
+                    items.push(value);
+                    break;
+                }
+            }
+        }
+        self.len += 1;
+    }
+
+    /// Removes the last item, dropping the nodes it leaves empty and the levels above a root with a single child
+    pub fn pop(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let value = Self::pop_from(Arc::make_mut(&mut self.root));
+        self.len -= 1;
+        while let Node::Branch(children) = &*self.root {
+            if children.len() != 1 {
+                break;
+            }
+            self.root = Arc::clone(&children[0]);
+            self.shift -= BITS;
+        }
+        value
+    }
+
+    fn pop_from(node: &mut Node<T>) -> Option<T> {
+        match node {
+            Node::Leaf(items) => items.pop(),
+            Node::Branch(children) => {
+                let last = children.last_mut()?;
+                let value = Self::pop_from(Arc::make_mut(last));
+                if matches!(&**last, Node::Leaf(items) if items.is_empty())
+                    || matches!(&**last, Node::Branch(nodes) if nodes.is_empty())
+                {
+                    children.pop();
+                }
+                value
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = SharedVec::default();
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().cloned().collect()
+    }
+
+    /// Copies the items in the range into a vector of their own, or `None` if the range is out of bounds
+    pub fn slice(&self, range: Range<usize>) -> Option<SharedVec<T>> {
+        if range.start > range.end || range.end > self.len {
+            return None;
+        }
+        Some(range.map(|idx| self[idx].clone()).collect())
+    }
+}
+
+impl<T> SharedVec<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        if idx >= self.len {
+            return None;
+        }
+
+        let (mut node, mut shift) = (&*self.root, self.shift);
+        loop {
+            match node {
+                Node::Branch(children) => {
+                    node = &children[(idx >> shift) & MASK];
+                    shift -= BITS;
+                }
+                Node::Leaf(items) => return items.get(idx & MASK),
+            }
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        (0..self.len).map(move |idx| &self[idx])
+    }
+
+    pub fn contains(&self, value: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        self.iter().any(|item| item == value)
+    }
+}
+
+impl<T> From<Vec<T>> for SharedVec<T> {
+    /// Splits the items into chunks and builds the levels of nodes above them until a single one is left
+    fn from(items: Vec<T>) -> SharedVec<T> {
+        let len = items.len();
+        let mut items = items.into_iter();
+        let mut nodes = vec![];
+        loop {
+            let chunk = items.by_ref().take(CHUNK).collect::<Vec<_>>();
+            if chunk.is_empty() {
+                break;
+            }
+            nodes.push(Arc::new(Node::Leaf(chunk)));
+        }
+
+        let mut shift = 0;
+        while nodes.len() > 1 {
+            shift += BITS;
+            let mut children = nodes.into_iter();
+            nodes = vec![];
+            loop {
+                let chunk = children.by_ref().take(CHUNK).collect::<Vec<_>>();
+                if chunk.is_empty() {
+                    break;
+                }
+                nodes.push(Arc::new(Node::Branch(chunk)));
+            }
+        }
+
+        let root = nodes.pop().unwrap_or_else(|| Arc::new(Node::Leaf(vec![])));
+        SharedVec { len, shift, root }
+    }
+}
+
+impl<T> FromIterator<T> for SharedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> SharedVec<T> {
+        SharedVec::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl<T: Clone> Extend<T> for SharedVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.push(item);
+        }
+    }
+}
+
+impl<'a, T: Copy + 'a> Extend<&'a T> for SharedVec<T> {
+    fn extend<I: IntoIterator<Item = &'a T>>(&mut self, iter: I) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+impl<T> Index<usize> for SharedVec<T> {
+    type Output = T;
+
+    fn index(&self, idx: usize) -> &T {
+        let len = self.len;
+        self.get(idx)
+            .unwrap_or_else(|| panic!("index out of bounds: the len is {} but the index is {}", len, idx))
+    }
+}
+
+impl<T: Clone> IndexMut<usize> for SharedVec<T> {
+    fn index_mut(&mut self, idx: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(idx)
+            .unwrap_or_else(|| panic!("index out of bounds: the len is {} but the index is {}", len, idx))
+    }
+}
+
+impl<T> Clone for SharedVec<T> {
+    /// Shares every item with the clone
+    fn clone(&self) -> Self {
+        SharedVec {
+            len: self.len,
+            shift: self.shift,
+            root: Arc::clone(&self.root),
+        }
+    }
+}
+
+impl<T> Default for SharedVec<T> {
+    fn default() -> Self {
+        SharedVec::from(vec![])
+    }
+}
+
+impl<T: PartialEq> PartialEq for SharedVec<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: Eq> Eq for SharedVec<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for SharedVec<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_on_every_level() {
+        for len in [0, 1, CHUNK, CHUNK + 1, CHUNK * CHUNK * 3 + 5] {
+            let mut items = (0..len).collect::<SharedVec<_>>();
+            assert_eq!(items.len(), len);
+            assert_eq!(items.iter().copied().collect::<Vec<_>>(), (0..len).collect::<Vec<_>>());
+            assert_eq!(items.get(len), None);
+
+            for idx in 0..len {
+                items[idx] *= 2;
+            }
+            assert!(items.iter().enumerate().all(|(idx, item)| *item == idx * 2));
+        }
+    }
+
+    #[test]
+    fn clones_share_items_until_written() {
+        let original = SharedVec::from_elem(0, 1000);
+        let mut clone = original.clone();
+        clone[500] = 1;
+
+        assert_eq!((original[500], clone[500]), (0, 1));
+        assert_eq!(original.iter().sum::<i32>(), 0);
+        // the clone copied the chunk of the item and the nodes above it, the other chunks are still shared
+        match (&*original.root, &*clone.root) {
+            (Node::Branch(original), Node::Branch(clone)) => {
+                assert!(Arc::ptr_eq(&original[0], &clone[0]));
+                assert!(!Arc::ptr_eq(&original[500 >> 8], &clone[500 >> 8]));
+            }
+            _ => panic!("1000 items don't fit in a chunk"),
+        }
+    }
+
+    #[test]
+    fn push_and_pop_across_levels() {
+        let mut items = SharedVec::default();
+        let len = CHUNK * CHUNK + 3;
+        for item in 0..len {
+            items.push(item);
+        }
+        assert_eq!(items, (0..len).collect());
+
+        let saved = items.clone();
+        for item in (0..len).rev() {
+            assert_eq!(items.pop(), Some(item));
+            assert_eq!(items.len(), item);
+            assert_eq!(items.get(item), None);
+        }
+        assert_eq!((items.pop(), items.shift), (None, 0));
+        assert_eq!(saved, (0..len).collect());
+
+        items.extend(&[7, 8]);
+        assert_eq!(items, SharedVec::from(vec![7, 8]));
+    }
+
+    #[test]
+    fn slice_copies_the_range() {
+        let items = (0..100).collect::<SharedVec<_>>();
+
+        assert_eq!(items.slice(20..40), Some((20..40).collect()));
+        assert_eq!(items.slice(100..100), Some(SharedVec::default()));
+        assert_eq!(items.slice(90..101), None);
+    }
+}
@@ -1,19 +1,52 @@
 use tui::{
     buffer::Buffer,
     layout::Rect,
-    style::Color,
+    style::{Color, Modifier, Style},
     widgets::{Block, Widget},
 };
 
 use crate::app::{
-    grid::{Grid, cell::Cell},
+    game::{GameView, Visibility},
+    grid::{cell::Cell, topology::Topology, weights::MIN_WEIGHT, Grid},
+    solvers::Search,
+    state::{SharedVec, NO_SET},
     utils::types::Pos,
 };
 
+/// Background color of the cells a generator or a solver highlights at a step
+pub const HIGHLIGHT_COLOR: Color = Color::Red;
+/// Background colors of the cells that belong to disjoint sets. A set gets a color by its id
+pub const SET_COLORS: [Color; 10] = [
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::Yellow,
+    Color::LightBlue,
+    Color::LightMagenta,
+    Color::LightCyan,
+    Color::LightYellow,
+    Color::Gray,
+    Color::DarkGray,
+];
+
+/// Colors of the markers and the trails of the players
+const PLAYER_COLORS: [(Color, Color); 2] = [(Color::Yellow, Color::DarkGray), (Color::LightCyan, Color::Blue)];
+const PLAYER_MARKERS: [&str; 2] = ["1", "2"];
+
+/// Background colors of the cells settled by a solver, the path it's found, and the shortest path next to it
+const EXPLORED_COLOR: Color = Color::Blue;
+const PATH_COLOR: Color = Color::Yellow;
+const SHORTEST_COLOR: Color = Color::Magenta;
+/// Costly cells are shaded brighter, from the darkest gray of the 256 color palette. Every weight skips two shades
+const WEIGHT_SHADES_START: u8 = 233;
+
 pub struct MazeContainer<'a> {
     pub block: Option<Block<'a>>,
     pub grid: &'a Grid,
-    pub highlights: &'a Vec<Pos>,
+    pub highlights: &'a SharedVec<Pos>,
+    pub sets: Option<&'a SharedVec<u32>>,
+    pub game: Option<&'a GameView>,
+    pub search: Option<&'a Search>,
 }
 
 impl<'a> Widget for MazeContainer<'a> {
@@ -27,6 +60,13 @@ impl<'a> Widget for MazeContainer<'a> {
             None => area,
         };
 
+        match self.grid.topology() {
+            Topology::Hexagonal => return self.display_hex_grid(widget_area, buf),
+            Topology::Triangular => return self.display_triangle_grid(widget_area, buf),
+            Topology::Polar => return self.display_polar_note(widget_area, buf),
+            Topology::Rectangular => (),
+        }
+
         // plus 1 to take into account the western maze wall
         let min_maze_height = self.grid.height() as u16 + 1;
         // plus 1 to take into account the western maze wall; multilple by 2 since one cell takes at least 2 characters
@@ -41,11 +81,14 @@ impl<'a> Widget for MazeContainer<'a> {
 }
 
 impl<'a> MazeContainer<'a> {
-    pub fn new(grid: &'a Grid, highlights: &'a Vec<Pos>) -> Self {
+    pub fn new(grid: &'a Grid, highlights: &'a SharedVec<Pos>) -> Self {
         MazeContainer {
             block: None,
             highlights,
             grid,
+            sets: None,
+            game: None,
+            search: None,
         }
     }
 
@@ -54,6 +97,26 @@ impl<'a> MazeContainer<'a> {
         self
     }
 
+    /// Colors every cell by the set it belongs to. An empty layer is ignored
+    pub fn sets(mut self, sets: &'a SharedVec<u32>) -> MazeContainer<'a> {
+        if !sets.is_empty() {
+            self.sets = Some(sets);
+        }
+        self
+    }
+
+    /// Shows the player, the exit and the cells the player has been in
+    pub fn game(mut self, game: &'a GameView) -> MazeContainer<'a> {
+        self.game = Some(game);
+        self
+    }
+
+    /// Shows the cells settled by a solver and the paths it's found
+    pub fn search(mut self, search: Option<&'a Search>) -> MazeContainer<'a> {
+        self.search = search;
+        self
+    }
+
     pub fn display_grid(&mut self, area: Rect, buf: &mut Buffer) {
         let grow_factor = self.get_grid_grow_factor(area, self.grid);
         let (x_margin, y_margin) = self.get_grid_margins(grow_factor, area, self.grid);
@@ -66,17 +129,36 @@ impl<'a> MazeContainer<'a> {
 
         // draw top border
         for x in 0..maze_width - 1 {
-            self.add_horizontal_wall((x_margin + x as u16 + 1, y_margin), None, buf);
+            let cx = (x as f64 / grow_factor as f64 / 2.0).floor() as usize;
+            let is_last_col = (x as f64 + 1.0) / grow_factor as f64 / 2.0 == cx as f64 + 1.0;
+
+            if self.has_wall((cx, 0), Cell::NORTH) || (is_last_col && self.has_wall((cx + 1, 0), Cell::NORTH)) {
+                self.add_horizontal_wall((x_margin + x + 1, y_margin), None, buf);
+            } else {
+                self.add_passage((x_margin + x + 1, y_margin), None, buf);
+            }
+            let cells = if is_last_col {
+                vec![(cx, 0), (cx + 1, 0)]
+            } else {
+                vec![(cx, 0)]
+            };
+            self.add_fog((x_margin + x + 1, y_margin), &cells, buf);
         }
 
         for y in 0..maze_height {
-            self.add_vertical_wall((x_margin, y_margin + y as u16 + 1), buf);
+            let cy = (y as f64 / grow_factor as f64).floor() as usize;
+            if self.has_wall((0, cy), Cell::WEST) {
+                self.add_vertical_wall((x_margin, y_margin + y + 1), buf);
+            } else {
+                self.add_passage((x_margin, y_margin + y + 1), None, buf);
+            }
+            self.add_fog((x_margin, y_margin + y + 1), &[(0, cy)], buf);
 
             for x in 0..maze_width {
                 // X coordinate including maring on the axis X
-                let nx = x_margin + x as u16 + 1;
+                let nx = x_margin + x + 1;
                 // Y coordinate including maring on the axis Y
-                let ny = y_margin + y as u16 + 1;
+                let ny = y_margin + y + 1;
                 // X coordinate of a cell in the grid
                 let cx = (x as f64 / grow_factor as f64 / 2.0).floor() as usize;
                 // Y coordinate of a cell in the grid
@@ -89,37 +171,191 @@ impl<'a> MazeContainer<'a> {
                 match (is_last_row, is_last_col) {
                     (false, false) => self.add_passage((nx, ny), Some((cx, cy)), buf),
                     (false, true) => {
-                        if self.grid.is_cell_carved((cx, cy), Cell::EAST) {
-                            self.add_passage((nx, ny), Some((cx, cy)), buf);
-                        } else {
+                        if self.has_wall((cx, cy), Cell::EAST) {
                             self.add_vertical_wall((nx, ny), buf);
+                        } else {
+                            self.add_passage((nx, ny), Some((cx, cy)), buf);
                         }
                     }
                     (true, false) => {
-                        if self.grid.is_cell_carved((cx, cy), Cell::SOUTH) {
-                            self.add_passage((nx, ny), Some((cx, cy)), buf);
-                        } else {
+                        if self.has_wall((cx, cy), Cell::SOUTH) {
                             self.add_horizontal_wall((nx, ny), Some((cx, cy)), buf);
+                        } else {
+                            self.add_passage((nx, ny), Some((cx, cy)), buf);
                         }
                     }
                     (true, true) => {
-                        if self.grid.is_cell_carved((cx, cy), Cell::EAST) {
-                            if self.grid.is_cell_carved((cx, cy), Cell::SOUTH)
-                                || self.next_cell_carved_south(cx, cy, self.grid)
-                            {
-                                self.add_passage((nx, ny), Some((cx, cy)), buf);
-                            } else {
-                                self.add_horizontal_wall((nx, ny), Some((cx, cy)), buf);
-                            }
-                        } else {
+                        if self.has_wall((cx, cy), Cell::EAST) {
                             self.add_vertical_wall((nx, ny), buf);
+                        } else if self.has_south_corner_wall(cx, cy) {
+                            self.add_horizontal_wall((nx, ny), Some((cx, cy)), buf);
+                        } else {
+                            self.add_passage((nx, ny), Some((cx, cy)), buf);
                         }
                     }
                 }
+
+                if x % (grow_factor * 2) == 0 && y % grow_factor == 0 {
+                    self.add_stairs((nx, ny), (cx, cy), buf);
+                    self.add_bridge((nx, ny), (cx, cy), buf);
+                    self.add_game_marker((nx, ny), (cx, cy), buf);
+                }
+
+                // a wall shows if any of the cells it separates does
+                let cells = match (is_last_row, is_last_col) {
+                    (false, false) => vec![(cx, cy)],
+                    (false, true) => vec![(cx, cy), (cx + 1, cy)],
+                    (true, false) => vec![(cx, cy), (cx, cy + 1)],
+                    (true, true) => vec![(cx, cy), (cx + 1, cy), (cx, cy + 1), (cx + 1, cy + 1)],
+                };
+                self.add_fog((nx, ny), &cells, buf);
+            }
+        }
+    }
+
+    /// Draws flat-topped hexagons, 4 characters wide and 3 lines high, sharing their walls with the neighbours:
+    ///
+    /// ```text
+    ///  __    __
+    /// /  \__/  \
+    /// \__/  \__/
+    ///    \__/
+    /// ```
+    pub fn display_hex_grid(&mut self, area: Rect, buf: &mut Buffer) {
+        let (width, height) = (self.grid.width() as u16, self.grid.height() as u16);
+        // odd columns are shifted one line down
+        let maze_width = width * 3 + 1;
+        let maze_height = height * 2 + 1 + u16::from(width > 1);
+        if area.width < maze_width || area.height < maze_height {
+            return;
+        }
+
+        let x_margin = area.left() + (area.width - maze_width) / 2;
+        let y_margin = area.top() + (area.height - maze_height) / 2;
+
+        for (cx, cy) in self.grid.positions() {
+            let x = x_margin + cx as u16 * 3;
+            let y = y_margin + cy as u16 * 2 + cx as u16 % 2;
+
+            // the inside of the hexagon and its bottom line take the cell background
+            for dx in 1..=2 {
+                self.add_passage((x + dx, y + 1), Some((cx, cy)), buf);
+                self.add_passage((x + dx, y + 2), Some((cx, cy)), buf);
+            }
+
+            for (direction, symbol, points) in [
+                (Cell::NORTH, "_", [(x + 1, y), (x + 2, y)]),
+                (Cell::SOUTH, "_", [(x + 1, y + 2), (x + 2, y + 2)]),
+                (Cell::NORTH_WEST, "/", [(x, y + 1); 2]),
+                (Cell::NORTH_EAST, "\\", [(x + 3, y + 1); 2]),
+                (Cell::SOUTH_WEST, "\\", [(x, y + 2); 2]),
+                (Cell::SOUTH_EAST, "/", [(x + 3, y + 2); 2]),
+            ] {
+                if self.grid.is_cell_carved((cx, cy), direction) {
+                    continue;
+                }
+
+                for (px, py) in points {
+                    buf.get_mut(px, py).set_fg(Color::Green).set_symbol(symbol);
+                }
             }
+
+            self.add_stairs((x + 1, y + 1), (cx, cy), buf);
         }
     }
 
+    /// Draws triangles 4 characters wide and 2 lines high, overlapping their neighbours by half:
+    ///
+    /// ```text
+    ///    __
+    ///  /\  /\
+    /// /__\/__\
+    /// ```
+    pub fn display_triangle_grid(&mut self, area: Rect, buf: &mut Buffer) {
+        let (width, height) = (self.grid.width() as u16, self.grid.height() as u16);
+        // plus 1 line for the bases of the triangles in the first row pointing down
+        let maze_width = width * 2 + 2;
+        let maze_height = height * 2 + 1;
+        if area.width < maze_width || area.height < maze_height {
+            return;
+        }
+
+        let x_margin = area.left() + (area.width - maze_width) / 2;
+        let y_margin = area.top() + (area.height - maze_height) / 2 + 1;
+
+        // cells in sight are drawn last, so the walls they share with the ones seen before stay bright
+        let mut positions = self
+            .grid
+            .positions()
+            .filter(|pos| self.get_visibility(*pos) != Visibility::Hidden)
+            .collect::<Vec<_>>();
+        positions.sort_by_key(|pos| self.get_visibility(*pos));
+
+        for (cx, cy) in positions {
+            let x = x_margin + cx as u16 * 2;
+            let y = y_margin + cy as u16 * 2;
+
+            let is_pointing_up = Topology::is_pointing_up((cx, cy));
+            let walls = if is_pointing_up {
+                for dx in 1..=2 {
+                    self.add_passage((x + dx, y + 1), Some((cx, cy)), buf);
+                }
+                [
+                    (Cell::WEST, "/", [(x + 1, y), (x, y + 1)]),
+                    (Cell::EAST, "\\", [(x + 2, y), (x + 3, y + 1)]),
+                    (Cell::SOUTH, "_", [(x + 1, y + 1), (x + 2, y + 1)]),
+                ]
+            } else {
+                for dx in 1..=2 {
+                    self.add_passage((x + dx, y), Some((cx, cy)), buf);
+                }
+                [
+                    (Cell::WEST, "\\", [(x, y), (x + 1, y + 1)]),
+                    (Cell::EAST, "/", [(x + 3, y), (x + 2, y + 1)]),
+                    (Cell::NORTH, "_", [(x + 1, y - 1), (x + 2, y - 1)]),
+                ]
+            };
+
+            for (direction, symbol, points) in walls {
+                let symbol = if self.grid.is_cell_carved((cx, cy), direction) {
+                    " "
+                } else {
+                    symbol
+                };
+
+                for (px, py) in points {
+                    buf.get_mut(px, py).set_fg(Color::Green).set_symbol(symbol);
+                }
+            }
+
+            let inside = if is_pointing_up { (x + 1, y + 1) } else { (x + 1, y) };
+            self.add_stairs(inside, (cx, cy), buf);
+            self.add_game_marker(inside, (cx, cy), buf);
+
+            for point in walls
+                .iter()
+                .flat_map(|(_, _, points)| *points)
+                .chain([inside, (inside.0 + 1, inside.1)])
+            {
+                self.add_fog(point, &[(cx, cy)], buf);
+            }
+        }
+    }
+
+    /// Rings cannot be drawn with characters, so polar mazes are only exported to SVG
+    pub fn display_polar_note(&mut self, area: Rect, buf: &mut Buffer) {
+        let note = format!(
+            "{} rings, {} cells. Polar mazes can only be exported to SVG",
+            self.grid.height(),
+            self.grid.cells_count()
+        );
+        let width = (note.chars().count() as u16).min(area.width);
+        let x = area.left() + (area.width - width) / 2;
+        let y = area.top() + area.height / 2;
+
+        buf.set_stringn(x, y, note, width as usize, Style::default().fg(Color::Green));
+    }
+
     fn get_grid_grow_factor(&self, area: Rect, grid: &Grid) -> u16 {
         let margin = 2.0;
 
@@ -149,10 +385,8 @@ impl<'a> MazeContainer<'a> {
         let cell = buf.get_mut(x, y);
         cell.set_fg(Color::Green).set_symbol("_");
 
-        if let Some((cx, cy)) = cell_pos {
-            if self.highlights.contains(&(cx, cy)) {
-                cell.set_bg(Color::Red);
-            }
+        if let Some(color) = cell_pos.and_then(|pos| self.get_cell_bg(pos)) {
+            cell.set_bg(color);
         }
     }
 
@@ -160,17 +394,368 @@ impl<'a> MazeContainer<'a> {
         let cell = buf.get_mut(x, y);
         cell.set_fg(Color::Green).set_symbol(" ");
 
-        if let Some((cx, cy)) = cell_pos {
-            if self.highlights.contains(&(cx, cy)) {
-                cell.set_bg(Color::Red);
+        if let Some(color) = cell_pos.and_then(|pos| self.get_cell_bg(pos)) {
+            cell.set_bg(color);
+        }
+    }
+
+    /// Marks cells with stairs: `↑` leads to the level above, `↓` to the one below and `↕` to both
+    fn add_stairs(&self, (x, y): (u16, u16), pos: Pos, buf: &mut Buffer) {
+        let symbol = match (
+            self.grid.is_cell_carved(pos, Cell::UP),
+            self.grid.is_cell_carved(pos, Cell::DOWN),
+        ) {
+            (true, true) => "↕",
+            (true, false) => "↑",
+            (false, true) => "↓",
+            (false, false) => return,
+        };
+
+        let cell = buf.get_mut(x, y);
+        // the marker takes the place of a bottom wall when cells are a single line high, so it underlines itself
+        if cell.symbol == "_" {
+            cell.set_style(Style::default().add_modifier(Modifier::UNDERLINED));
+        }
+        cell.set_fg(Color::White).set_symbol(symbol);
+    }
+
+    /// Marks crossings with the passage going over them: `═` runs from west to east, `║` from north to south
+    fn add_bridge(&self, (x, y): (u16, u16), pos: Pos, buf: &mut Buffer) {
+        if !self.grid.is_cell_carved(pos, Cell::UNDER) {
+            return;
+        }
+
+        let symbol = if self.grid.is_cell_carved(pos, Cell::EAST) {
+            "═"
+        } else {
+            "║"
+        };
+        buf.get_mut(x, y).set_fg(Color::White).set_symbol(symbol);
+    }
+
+    /// Covers the character with fog unless one of the cells it belongs to has been seen. Cells seen before are dimmed
+    fn add_fog(&self, (x, y): (u16, u16), cells: &[Pos], buf: &mut Buffer) {
+        let visibility = cells
+            .iter()
+            .map(|pos| self.get_visibility(*pos))
+            .max()
+            .unwrap_or(Visibility::Visible);
+
+        let cell = buf.get_mut(x, y);
+        match visibility {
+            Visibility::Visible => (),
+            Visibility::Seen => {
+                cell.set_style(Style::default().fg(Color::DarkGray).bg(Color::Reset));
+            }
+            Visibility::Hidden => {
+                cell.set_style(Style::reset()).set_symbol(" ");
+            }
+        }
+    }
+
+    /// Cells are visible unless the game covers the maze with fog
+    fn get_visibility(&self, (cx, cy): Pos) -> Visibility {
+        match self.game {
+            Some(game) if !game.visibility.is_empty() => {
+                if cx >= self.grid.width() || cy >= self.grid.height() {
+                    return Visibility::Hidden;
+                }
+                game.visibility[cy * self.grid.width() + cx]
+            }
+            _ => Visibility::Visible,
+        }
+    }
+
+    /// Marks the player with `@` and the exit with `X`. Racing players are marked with their numbers
+    fn add_game_marker(&self, (x, y): (u16, u16), pos: Pos, buf: &mut Buffer) {
+        let game = match self.game {
+            Some(game) => game,
+            None => return,
+        };
+
+        let player = game.players.iter().position(|player| *player == Some(pos));
+        let (symbol, color) = match player {
+            Some(_) if game.players.len() == 1 => ("@", PLAYER_COLORS[0].0),
+            Some(player) => (
+                PLAYER_MARKERS[player % PLAYER_MARKERS.len()],
+                PLAYER_COLORS[player % PLAYER_COLORS.len()].0,
+            ),
+            None if game.exit == Some(pos) => ("X", Color::LightRed),
+            None => return,
+        };
+        let cell = buf.get_mut(x, y);
+        let mut style = Style::default().fg(color).add_modifier(Modifier::BOLD);
+        // like stairs, the marker underlines itself when it takes the place of a bottom wall
+        if cell.symbol == "_" {
+            style = style.add_modifier(Modifier::UNDERLINED);
+        }
+        cell.set_style(style).set_symbol(symbol);
+    }
+
+    fn get_cell_bg(&self, (cx, cy): Pos) -> Option<Color> {
+        if self.highlights.contains(&(cx, cy)) {
+            return Some(HIGHLIGHT_COLOR);
+        }
+
+        let idx = cy * self.grid.width() + cx;
+        let player = self
+            .game
+            .and_then(|game| game.trails.iter().position(|trail| trail.get(idx) == Some(&true)));
+        if let Some(player) = player {
+            return Some(PLAYER_COLORS[player % PLAYER_COLORS.len()].1);
+        }
+
+        if let Some(search) = self.search {
+            if search.path.contains(&(cx, cy)) {
+                return Some(PATH_COLOR);
             }
+            if search.shortest.contains(&(cx, cy)) {
+                return Some(SHORTEST_COLOR);
+            }
+            if search.explored.get(idx) == Some(&true) {
+                return Some(EXPLORED_COLOR);
+            }
+        }
+
+        if let Some(set) = self.sets.and_then(|sets| sets.get(idx)).filter(|set| **set != NO_SET) {
+            return Some(SET_COLORS[*set as usize % SET_COLORS.len()]);
+        }
+
+        match self.grid.weight((cx, cy)) {
+            MIN_WEIGHT => None,
+            weight => Some(Color::Indexed(WEIGHT_SHADES_START + weight as u8 * 2)),
+        }
+    }
+
+    /// Checks if the cell has a wall on the side. Cells switched off by a mask only have walls next to the cells
+    /// that are on. Passages across wrapped edges leave openings in the border, tunnels under crossings in the cell
+    fn has_wall(&self, pos: Pos, direction: Cell) -> bool {
+        if self.grid.contains(pos) {
+            return !self.grid.is_cell_carved(pos, direction) && !self.grid.tunnels_under(pos, direction);
+        }
+        self.grid.get_next_cell_pos(pos, direction).is_ok() && !self.grid.wraps_around(pos, direction)
+    }
+
+    /// Checks if the bottom right corner of the cell continues a south wall. Between two cells that are on it only
+    /// does if both of them have one, otherwise the wall of either cell reaches the corner
+    fn has_south_corner_wall(&self, cx: usize, cy: usize) -> bool {
+        let (pos, next) = ((cx, cy), (cx + 1, cy));
+        if cx + 1 >= self.grid.width() {
+            return self.has_wall(pos, Cell::SOUTH);
+        }
+
+        if self.grid.contains(pos) && self.grid.contains(next) {
+            self.has_wall(pos, Cell::SOUTH) && self.has_wall(next, Cell::SOUTH)
+        } else {
+            self.has_wall(pos, Cell::SOUTH) || self.has_wall(next, Cell::SOUTH)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, path::PathBuf};
+
+    use tui::{backend::TestBackend, Terminal};
+
+    use super::*;
+    use crate::app::{
+        algos::{find, IGenerator, RecursiveBacktracking},
+        game::{Fog, Game},
+        grid::{mask::Mask, wrap::Wrap},
+    };
+
+    /// Set this variable to rewrite the golden files with the current rendering
+    const UPDATE_ENV: &str = "UPDATE_GOLDEN";
+
+    /// Renders the final maze of the generator with the id into a terminal that fits the maze with the given grow factor
+    fn render(id: &str, (width, height): (usize, usize), seed: u64, grow_factor: u16) -> String {
+        // the smallest area for the grow factor, see `get_grid_grow_factor`
+        let area_width = (width as u16 * 2 + 2) * grow_factor;
+        let area_height = (height as u16 + 2) * grow_factor;
+
+        draw(id, Grid::new(width, height), seed, (area_width, area_height))
+    }
+
+    fn draw(id: &str, grid: Grid, seed: u64, (area_width, area_height): (u16, u16)) -> String {
+        let snapshots = find(id).unwrap().generate(grid, seed);
+        let grid = snapshots.last().unwrap().get_grid();
+        let highlights = SharedVec::default();
+
+        let mut terminal = Terminal::new(TestBackend::new(area_width, area_height)).unwrap();
+        terminal
+            .draw(|f| f.render_widget(MazeContainer::new(grid, &highlights), f.size()))
+            .unwrap();
+        buffer_lines(&terminal)
+    }
+
+    fn buffer_lines(terminal: &Terminal<TestBackend>) -> String {
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                let line = (0..buffer.area.width)
+                    .map(|x| buffer.get(x, y).symbol.as_str())
+                    .collect::<String>();
+                line.trim_end().to_string() + "\n"
+            })
+            .collect()
+    }
+
+    fn assert_golden(name: &str, rendered: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/app/widgets/golden")
+            .join(format!("{}.txt", name));
+
+        if env::var_os(UPDATE_ENV).is_some() {
+            fs::write(&path, rendered).unwrap();
+            return;
         }
+
+        let expected = fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("Missing golden file {:?}. Run the tests with {}=1", path, UPDATE_ENV));
+        assert!(
+            expected == rendered,
+            "Rendering of {} differs from {:?}\n\nExpected:\n{}\nRendered:\n{}",
+            name,
+            path,
+            expected,
+            rendered
+        );
+    }
+
+    #[test]
+    fn recursive_backtracker_grow_factors() {
+        for grow_factor in 1..=3 {
+            let rendered = render("recursive-backtracker", (6, 4), 1, grow_factor);
+            assert_golden(&format!("recursive_backtracker_6x4_x{}", grow_factor), &rendered);
+        }
+    }
+
+    #[test]
+    fn wide_maze() {
+        // a maze wider than high catches corners computed against the wrong dimension
+        let rendered = render("kruskal", (12, 5), 7, 1);
+        assert_golden("kruskal_12x5_x1", &rendered);
     }
 
-    fn next_cell_carved_south(&self, cx: usize, cy: usize, grid: &Grid) -> bool {
-        if cx + 1 >= grid.height() {
-            return false;
+    #[test]
+    fn tall_maze() {
+        let rendered = render("prim", (4, 9), 3, 2);
+        assert_golden("prim_4x9_x2", &rendered);
+    }
+
+    #[test]
+    fn single_row_and_column() {
+        assert_golden("sidewinder_7x1_x2", &render("sidewinder", (7, 1), 5, 2));
+        assert_golden("eller_1x5_x1", &render("eller", (1, 5), 5, 1));
+    }
+
+    #[test]
+    fn single_cell() {
+        assert_golden("hunt_and_kill_1x1_x3", &render("hunt-and-kill", (1, 1), 0, 3));
+    }
+
+    #[test]
+    fn hexagonal_maze() {
+        let grid = Grid::with_topology(6, 4, Topology::Hexagonal);
+        assert_golden(
+            "recursive_backtracker_hex_6x4",
+            &draw("recursive-backtracker", grid, 1, (19, 10)),
+        );
+    }
+
+    #[test]
+    fn triangular_maze() {
+        let grid = Grid::with_topology(7, 4, Topology::Triangular);
+        assert_golden("kruskal_triangular_7x4", &draw("kruskal", grid, 2, (16, 9)));
+    }
+
+    #[test]
+    fn masked_maze() {
+        let mask = Mask::from_ascii(".####.\n##..##\n##..##\n.####.").unwrap();
+        let grid = Grid::with_mask(&mask, Topology::Rectangular).unwrap();
+        assert_golden("kruskal_masked_6x4_x1", &draw("kruskal", grid, 4, (14, 6)));
+    }
+
+    #[test]
+    fn torus_maze() {
+        // passages across the edges leave openings in the border
+        let grid = Grid::new(6, 4).wrapped(Wrap::Both);
+        assert_golden("prim_torus_6x4_x1", &draw("prim", grid, 6, (14, 6)));
+    }
+
+    #[test]
+    fn stacked_maze_level() {
+        let snapshots = RecursiveBacktracking::init(Grid::new(6, 4).stacked(3), 3).run();
+        let level = snapshots.last().unwrap().level(1);
+        let highlights = SharedVec::default();
+
+        let mut terminal = Terminal::new(TestBackend::new(14, 6)).unwrap();
+        terminal
+            .draw(|f| f.render_widget(MazeContainer::new(level.get_grid(), &highlights), f.size()))
+            .unwrap();
+        assert_golden("recursive_backtracker_level_2_of_6x4x3_x1", &buffer_lines(&terminal));
+    }
+
+    #[test]
+    fn weave_maze() {
+        assert_golden("kruskal_weave_8x5_x2", &render("kruskal-weave", (8, 5), 2, 2));
+    }
+
+    #[test]
+    fn game_markers() {
+        let snapshots = RecursiveBacktracking::init(Grid::new(6, 4), 1).run();
+        let grid = snapshots.last().unwrap().get_grid();
+        let mut game = Game::new(grid.clone()).unwrap();
+        assert!(game.step(0, grid.carved_directions((0, 0))[0]));
+        let view = game.view(0);
+        let highlights = SharedVec::default();
+
+        let mut terminal = Terminal::new(TestBackend::new(14, 6)).unwrap();
+        terminal
+            .draw(|f| f.render_widget(MazeContainer::new(grid, &highlights).game(&view), f.size()))
+            .unwrap();
+        assert_golden("recursive_backtracker_game_6x4_x1", &buffer_lines(&terminal));
+    }
+
+    #[test]
+    fn race_markers() {
+        let snapshots = RecursiveBacktracking::init(Grid::new(6, 4), 1).run();
+        let grid = snapshots.last().unwrap().get_grid();
+        let mut game = Game::new(grid.clone()).unwrap().with_players(2);
+        assert!(game.step(1, grid.carved_directions((0, 0))[0]));
+        let view = game.view(0);
+        let highlights = SharedVec::default();
+
+        let mut terminal = Terminal::new(TestBackend::new(14, 6)).unwrap();
+        terminal
+            .draw(|f| f.render_widget(MazeContainer::new(grid, &highlights).game(&view), f.size()))
+            .unwrap();
+        assert_golden("recursive_backtracker_race_6x4_x1", &buffer_lines(&terminal));
+    }
+
+    #[test]
+    fn fog_of_war() {
+        for (name, grid, area) in [
+            ("recursive_backtracker_fog_6x4_x2", Grid::new(6, 4), (26, 12)),
+            (
+                "recursive_backtracker_fog_triangular_7x4",
+                Grid::with_topology(7, 4, Topology::Triangular),
+                (16, 9),
+            ),
+        ] {
+            let snapshots = RecursiveBacktracking::init(grid, 1).run();
+            let grid = snapshots.last().unwrap().get_grid();
+            let mut game = Game::new(grid.clone()).unwrap().with_fog(Fog::Steps(2));
+            assert!(game.step(0, grid.carved_directions((0, 0))[0]));
+            let view = game.view(0);
+            let highlights = SharedVec::default();
+
+            let mut terminal = Terminal::new(TestBackend::new(area.0, area.1)).unwrap();
+            terminal
+                .draw(|f| f.render_widget(MazeContainer::new(grid, &highlights).game(&view), f.size()))
+                .unwrap();
+            assert_golden(name, &buffer_lines(&terminal));
         }
-        grid.is_cell_carved((cx + 1, cy), Cell::SOUTH)
     }
 }
@@ -0,0 +1,315 @@
+//! Measures the generators without bringing up the UI. It has a binary of its own, so the allocator tracking the peak
+//! memory doesn't slow the game down
+mod alloc;
+
+use std::{fmt, str::FromStr, time::Duration, time::Instant};
+
+use argh::FromArgs;
+
+use daedalus::app::{
+    algos::{find, generators},
+    grid::{validation::validate_perfect_maze, Grid},
+    stats::MazeStats,
+};
+
+use self::alloc::TrackingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: TrackingAllocator = TrackingAllocator;
+
+static DEFAULT_SIZES: [Size; 3] = [Size(10, 10), Size(25, 25), Size(45, 45)];
+static DEFAULT_SEEDS: [u64; 3] = [1, 2, 3];
+
+#[derive(Debug, FromArgs)]
+#[argh(
+    description = "Runs every algorithm over a matrix of grid sizes and seeds and reports timings and maze statistics."
+)]
+pub struct BenchArgs {
+    /// number of runs per algorithm, size and seed. defaults to: 5
+    #[argh(option, short = 'n', default = "5")]
+    runs: usize,
+    /// grid size as WIDTHxHEIGHT, can be repeated. defaults to: 10x10, 25x25, 45x45
+    #[argh(option, short = 's')]
+    size: Vec<Size>,
+    /// id of the algorithm to run, e.g. kruskal, can be repeated. defaults to: every algorithm
+    #[argh(option, short = 'a')]
+    algo: Vec<String>,
+    /// random seed, can be repeated. defaults to: 1, 2, 3
+    #[argh(option)]
+    seed: Vec<u64>,
+    /// output format: table or csv. defaults to: table
+    #[argh(option, short = 'f', default = "Format::Table")]
+    format: Format,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Size(usize, usize);
+
+impl FromStr for Size {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || format!("Invalid size '{}'. Expected WIDTHxHEIGHT, e.g. 20x10", s);
+
+        let (width, height) = s.split_once('x').ok_or_else(error)?;
+        let width = width.trim().parse::<usize>().map_err(|_| error())?;
+        let height = height.trim().parse::<usize>().map_err(|_| error())?;
+
+        if width == 0 || height == 0 {
+            return Err(format!("Size '{}' must be at least 1x1", s));
+        }
+
+        Ok(Size(width, height))
+    }
+}
+
+impl fmt::Display for Size {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}x{}", self.0, self.1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("Unknown format '{}'. Expected table or csv", s)),
+        }
+    }
+}
+
+/// Measurements of one algorithm for one size and seed
+struct Record<'a> {
+    algorithm: &'a str,
+    size: Size,
+    seed: u64,
+    runs: usize,
+    mean_time: Duration,
+    min_time: Duration,
+    snapshots: usize,
+    peak_memory: usize,
+    perfect: bool,
+    stats: MazeStats,
+}
+
+impl<'a> Record<'a> {
+    const HEADER: [&'static str; 14] = [
+        "algorithm",
+        "size",
+        "seed",
+        "runs",
+        "mean_ms",
+        "min_ms",
+        "snapshots",
+        "peak_kib",
+        "perfect",
+        "dead_ends_pct",
+        "junctions",
+        "longest_path",
+        "avg_corridor_len",
+        "river_factor",
+    ];
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.algorithm.to_string(),
+            self.size.to_string(),
+            self.seed.to_string(),
+            self.runs.to_string(),
+            format!("{:.3}", self.mean_time.as_secs_f64() * 1000.0),
+            format!("{:.3}", self.min_time.as_secs_f64() * 1000.0),
+            self.snapshots.to_string(),
+            (self.peak_memory / 1024).to_string(),
+            (if self.perfect { "yes" } else { "no" }).to_string(),
+            format!("{:.1}", self.stats.dead_ends_pct()),
+            self.stats.junctions.to_string(),
+            self.stats.longest_path.to_string(),
+            format!("{:.2}", self.stats.avg_corridor_len),
+            format!("{:.2}", self.stats.river_factor),
+        ]
+    }
+}
+
+fn main() {
+    let args: BenchArgs = argh::from_env();
+
+    let sizes = if args.size.is_empty() {
+        DEFAULT_SIZES.to_vec()
+    } else {
+        args.size
+    };
+    let seeds = if args.seed.is_empty() {
+        DEFAULT_SEEDS.to_vec()
+    } else {
+        args.seed
+    };
+    let descriptors = if args.algo.is_empty() {
+        generators()
+    } else {
+        args.algo
+            .iter()
+            .map(|id| find(id).unwrap_or_else(|| panic!("Unknown algorithm '{}'.", id)))
+            .collect()
+    };
+    let runs = args.runs.max(1);
+
+    let mut records = vec![];
+    for descriptor in descriptors.iter() {
+        for size in sizes.iter() {
+            for seed in seeds.iter() {
+                let mut times = vec![];
+                let mut peak_memory = 0;
+                let mut snapshots = vec![];
+
+                for _ in 0..runs {
+                    // drop the previous run before measuring the next one
+                    drop(snapshots);
+
+                    let baseline = alloc::reset_peak();
+                    let start = Instant::now();
+                    snapshots = descriptor.generate(Grid::new(size.0, size.1), *seed);
+                    times.push(start.elapsed());
+                    peak_memory = peak_memory.max(alloc::peak() - baseline);
+                }
+
+                let grid = snapshots.last().map(|snapshot| snapshot.get_grid());
+                let stats = grid.map(MazeStats::from_grid).unwrap_or_default();
+                let perfect = grid.is_some_and(|grid| validate_perfect_maze(grid).is_ok());
+
+                records.push(Record {
+                    algorithm: descriptor.name,
+                    size: *size,
+                    seed: *seed,
+                    runs,
+                    mean_time: times.iter().sum::<Duration>() / runs as u32,
+                    min_time: times.iter().min().copied().unwrap_or_default(),
+                    snapshots: snapshots.len(),
+                    peak_memory,
+                    perfect,
+                    stats,
+                });
+            }
+        }
+    }
+
+    let output = match args.format {
+        Format::Table => format_table(&records),
+        Format::Csv => format_csv(&records),
+    };
+    print!("{}", output);
+}
+
+fn format_csv(records: &[Record]) -> String {
+    let mut output = Record::HEADER.join(",") + "\n";
+    for record in records {
+        let fields = record
+            .fields()
+            .into_iter()
+            .map(|field| {
+                if field.contains(',') {
+                    format!("\"{}\"", field)
+                } else {
+                    field
+                }
+            })
+            .collect::<Vec<_>>();
+        output += &(fields.join(",") + "\n");
+    }
+    output
+}
+
+fn format_table(records: &[Record]) -> String {
+    let rows = records.iter().map(Record::fields).collect::<Vec<_>>();
+
+    let mut widths = Record::HEADER.map(str::len);
+    for row in rows.iter() {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+
+    let format_row = |fields: Vec<String>| {
+        let cells = fields
+            .iter()
+            .zip(widths)
+            .enumerate()
+            // the algorithm name is aligned to the left, numbers are aligned to the right
+            .map(|(idx, (field, width))| match idx {
+                0 => format!("{:<width$}", field, width = width),
+                _ => format!("{:>width$}", field, width = width),
+            })
+            .collect::<Vec<_>>();
+        cells.join("  ").trim_end().to_string() + "\n"
+    };
+
+    let mut output = format_row(Record::HEADER.map(String::from).to_vec());
+    for row in rows {
+        output += &format_row(row);
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(algorithm: &str) -> Record<'_> {
+        Record {
+            algorithm,
+            size: Size(2, 3),
+            seed: 7,
+            runs: 2,
+            mean_time: Duration::from_micros(1500),
+            min_time: Duration::from_millis(1),
+            snapshots: 10,
+            peak_memory: 2048,
+            perfect: true,
+            stats: MazeStats::default(),
+        }
+    }
+
+    #[test]
+    fn parse_size() {
+        assert_eq!("20x10".parse::<Size>(), Ok(Size(20, 10)));
+        assert!("20".parse::<Size>().is_err());
+        assert!("0x10".parse::<Size>().is_err());
+        assert!("ax10".parse::<Size>().is_err());
+    }
+
+    #[test]
+    fn parse_format() {
+        assert_eq!("csv".parse::<Format>(), Ok(Format::Csv));
+        assert_eq!("table".parse::<Format>(), Ok(Format::Table));
+        assert!("json".parse::<Format>().is_err());
+    }
+
+    #[test]
+    fn csv_output() {
+        let output = format_csv(&[record("Prim's")]);
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], Record::HEADER.join(","));
+        assert_eq!(lines[1], "Prim's,2x3,7,2,1.500,1.000,10,2,yes,0.0,0,0,0.00,0.00");
+    }
+
+    #[test]
+    fn table_output_is_aligned() {
+        let output = format_table(&[record("Prim's"), record("Recursive Backtracker")]);
+        let lines = output.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        // the algorithm column is as wide as the longest name, the size column is as wide as its header
+        assert!(lines[1].starts_with(&format!("{:<21}  {:>4}", "Prim's", "2x3")));
+        assert_eq!(lines[1].len(), lines[2].len());
+    }
+}
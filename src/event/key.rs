@@ -17,6 +17,10 @@ pub enum Key {
     Up,
     /// Down arrow
     Down,
+    /// Page Up key
+    PageUp,
+    /// Page Down key
+    PageDown,
     Char(char),
     Ctrl(char),
     Alt(char),
@@ -33,7 +37,7 @@ impl fmt::Display for Key {
             Key::Ctrl(c) => write!(f, "<Ctrl+{}>", c),
             Key::Char(c) => write!(f, "{}", c),
             Key::Left | Key::Right | Key::Up | Key::Down => write!(f, "<{:?} Arrow Key>", self),
-            Key::Enter | Key::Tab | Key::Esc => write!(f, "<{:?}>", self),
+            Key::Enter | Key::Tab | Key::Esc | Key::PageUp | Key::PageDown => write!(f, "<{:?}>", self),
             _ => write!(f, "{:?}", self),
         }
     }
@@ -62,6 +66,14 @@ impl From<event::KeyEvent> for Key {
                 code: event::KeyCode::Down,
                 ..
             } => Key::Down,
+            event::KeyEvent {
+                code: event::KeyCode::PageUp,
+                ..
+            } => Key::PageUp,
+            event::KeyEvent {
+                code: event::KeyCode::PageDown,
+                ..
+            } => Key::PageDown,
             event::KeyEvent {
                 code: event::KeyCode::Enter,
                 ..
@@ -0,0 +1,5 @@
+//! Daedalus generates mazes in the terminal and plays back every step of the algorithms carving and solving them.
+//! Solvers of your own implement [`app::solvers::ISolver`] and join the solver menu with [`app::App::with_solver`]
+pub mod app;
+pub mod event;
+pub mod terminal;
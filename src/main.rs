@@ -1,15 +1,26 @@
-mod app;
-mod event;
-mod terminal;
-
+use daedalus::{
+    app::{
+        algos::{generators, Descriptor},
+        game::Fog,
+        grid::{
+            mask::Mask,
+            topology::Topology,
+            weights::{Terrain, Weights},
+            wrap::Wrap,
+        },
+        App,
+    },
+    terminal,
+};
 use argh::FromArgs;
 use eyre::Result;
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
 static MAZE_MIN_WIDTH: usize = 1;
 static MAZE_MIN_HEIGHT: usize = 1;
 static MAZE_MAX_WIDTH: usize = 45;
 static MAZE_MAX_HEIGHT: usize = 45;
+static MAZE_MAX_LEVELS: usize = 9;
 
 #[derive(Debug, FromArgs)]
 #[argh(description = "Daedalus is a maze generator for the terminal written in Rust. It can create mazes using different algorithms and show the process step-by-step. Simple as that.")]
@@ -23,33 +34,139 @@ struct Cli {
     /// grid height in characters. defaults to: 10
     #[argh(option, short = 'h', default = "10")]
     height: usize,
+    /// grid topology: rect, hex, tri or polar. polar grids have as many rings as the height. defaults to: rect
+    #[argh(option, short = 'g', default = "Topology::Rectangular")]
+    topology: Topology,
+    /// text template or PBM image shaping the maze. dots, spaces and white pixels switch cells off. the mask size
+    /// replaces the grid width and height
+    #[argh(option, short = 'm')]
+    mask: Option<PathBuf>,
+    /// grid edges leading to the opposite ones: none, horizontal (cylinder), vertical or both (torus). defaults to: none
+    #[argh(option, default = "Wrap::None")]
+    wrap: Wrap,
+    /// number of levels stacked on top of each other, linked by stairs. polar grids have one level. defaults to: 1
+    #[argh(option, short = 'l', default = "1")]
+    levels: usize,
+    /// share of the dead ends to remove after the maze is generated, from 0 (perfect maze) to 1 (braid maze).
+    /// defaults to: 0
+    #[argh(option, short = 'b', default = "0.0")]
+    braid: f64,
+    /// how far the player sees when playing in the fog: sight (along straight passages) or a number of steps.
+    /// defaults to: sight
+    #[argh(option, default = "Fog::LineOfSight")]
+    fog: Fog,
+    /// costs of walking through the cells: random (hills raised with the seed of the maze) or a text template with a
+    /// cost from 1 to 9 per character. dots and spaces cost 1
+    #[argh(option)]
+    weights: Option<String>,
+    /// algorithm selected at start by its id, e.g. kruskal or eller. see the README for all of them. defaults to: the
+    /// first one
+    #[argh(option, short = 'a')]
+    algo: Option<String>,
+    /// value of an algorithm parameter as NAME=VALUE, e.g. density=0.5 for Kruskal's (weave), can be repeated.
+    /// defaults to: the default of the parameter
+    #[argh(option, short = 'p')]
+    param: Vec<String>,
+    /// seed of the generated mazes, so others can generate and play the same ones. defaults to: a random seed per maze
+    #[argh(option)]
+    seed: Option<u64>,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli: Cli = argh::from_env();
-    validate_cli_options(&cli);
+
+    let mask = match &cli.mask {
+        Some(path) => Some(Mask::load(path).map_err(|err| eyre::eyre!("{}", err))?),
+        None => None,
+    };
+    let terrain = match cli.weights.as_deref() {
+        Some("random") => Some(Terrain::Random),
+        Some(path) => Some(Terrain::Map(
+            Weights::load(path.as_ref()).map_err(|err| eyre::eyre!("{}", err))?,
+        )),
+        None => None,
+    };
+    let (width, height) = match &mask {
+        Some(mask) => (mask.width(), mask.height()),
+        None => (cli.width, cli.height),
+    };
+
+    validate_maze_size(width, height);
+
+    if cli.levels < 1 || cli.levels > MAZE_MAX_LEVELS {
+        panic!("Maze must have from 1 to {} levels.", MAZE_MAX_LEVELS);
+    }
+
+    if !(0.0..=1.0).contains(&cli.braid) {
+        panic!("Braid ratio must be from 0 to 1.");
+    }
+
+    let generators = generators();
+    if let Some(id) = &cli.algo {
+        if !generators.iter().any(|descriptor| descriptor.id == *id) {
+            let ids = generators.iter().map(|descriptor| descriptor.id).collect::<Vec<_>>();
+            panic!("Unknown algorithm '{}'. Expected one of: {}.", id, ids.join(", "));
+        }
+    }
+    let params = cli.param.iter().map(|param| parse_param(param, &generators)).collect();
 
     let tick_rate = Duration::from_millis(cli.tick_rate);
-    terminal::run(tick_rate, cli.width, cli.height).await?;
+    let app = App::new(
+        "Maze Generator",
+        width,
+        height,
+        cli.topology,
+        mask,
+        cli.wrap,
+        cli.levels,
+    )
+    .with_braid(cli.braid)
+    .with_fog(cli.fog)
+    .with_terrain(terrain)
+    .with_seed(cli.seed)
+    .with_algo(cli.algo.as_deref())
+    .with_params(params);
+    terminal::run(tick_rate, app).await?;
 
     Ok(())
 }
 
-fn validate_cli_options(cli: &Cli) {
-    if cli.width < MAZE_MIN_WIDTH {
+/// Reads NAME=VALUE into a value of a parameter one of the generators takes, within its range
+fn parse_param(param: &str, generators: &[Descriptor]) -> (String, f64) {
+    let (name, value) = match param.split_once('=') {
+        Some((name, value)) => (name.trim(), value.trim()),
+        None => panic!("Invalid parameter '{}'. Expected NAME=VALUE, e.g. density=0.5.", param),
+    };
+    let parameter = generators
+        .iter()
+        .flat_map(|descriptor| descriptor.parameters)
+        .find(|parameter| parameter.name == name)
+        .unwrap_or_else(|| panic!("No algorithm takes a parameter named '{}'.", name));
+
+    match value.parse::<f64>() {
+        Ok(value) if (parameter.min..=parameter.max).contains(&value) => (name.to_string(), value),
+        _ => panic!(
+            "Parameter {} must be a number from {} to {}.",
+            name, parameter.min, parameter.max
+        ),
+    }
+}
+
+fn validate_maze_size(width: usize, height: usize) {
+    if width < MAZE_MIN_WIDTH {
         panic!("Maze width must take at least {} cell.", MAZE_MIN_WIDTH);
     }
 
-    if cli.height < MAZE_MIN_HEIGHT {
+    if height < MAZE_MIN_HEIGHT {
         panic!("Maze height must take at least {} cell.", MAZE_MIN_HEIGHT);
     }
 
-    if cli.width > MAZE_MAX_WIDTH {
+    if width > MAZE_MAX_WIDTH {
         panic!("Maze width can not be greater than {} cells.", MAZE_MAX_WIDTH);
     }
 
-    if cli.height > MAZE_MAX_HEIGHT {
+    if height > MAZE_MAX_HEIGHT {
         panic!("Maze height can not be greater than {} cells.", MAZE_MAX_HEIGHT);
     }
 }
@@ -16,7 +16,7 @@ use tui::{
     Terminal,
 };
 
-pub async fn run(tick_rate: Duration, width: usize, height: usize) -> Result<()> {
+pub async fn run(tick_rate: Duration, app: App<'_>) -> Result<()> {
     // setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
@@ -24,8 +24,7 @@ pub async fn run(tick_rate: Duration, width: usize, height: usize) -> Result<()>
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    // create app
-    let app = Arc::new(tokio::sync::Mutex::new(App::new("Maze Generator", width, height)));
+    let app = Arc::new(tokio::sync::Mutex::new(app));
     let app_ui = Arc::clone(&app);
 
     // configure logger
@@ -37,11 +36,7 @@ pub async fn run(tick_rate: Duration, width: usize, height: usize) -> Result<()>
 
     // restore terminal
     disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen, DisableMouseCapture)?;
     terminal.clear()?;
     terminal.show_cursor()?;
 